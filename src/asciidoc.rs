@@ -0,0 +1,104 @@
+//! Rendering of tables as AsciiDoc `|===` tables.
+
+use crate::table::{cell_lines, pad_cell};
+use crate::{Alignment, Table};
+use unicode_width::UnicodeWidthStr;
+
+/// Renders a table as an AsciiDoc table with a header row.
+///
+/// Column alignment becomes the `cols` attribute (`<`, `^`, `>`), pipes in
+/// cell content are escaped and line breaks become hard breaks (` +`).
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Name | Qty |\n|-|-:|\n| tea | 1 |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::asciidoc::write_table(&table),
+///     "[cols=\"1,>\",options=\"header\"]\n|===\n| Name | Qty\n\n| tea  | 1\n|===\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table) -> String {
+    let num_columns = table.column_count();
+    let rows: Vec<Vec<String>> = std::iter::once(&table.header)
+        .chain(&table.rows)
+        .map(|row| {
+            (0..num_columns)
+                .map(|col_i| escape_cell(row.get(col_i).map_or("", String::as_str)))
+                .collect()
+        })
+        .collect();
+
+    let mut widths = vec![0; num_columns];
+    for row in &rows {
+        for (col_i, cell) in row.iter().enumerate() {
+            if !cell.contains('\n') {
+                widths[col_i] = widths[col_i].max(cell.width());
+            }
+        }
+    }
+
+    let cols: Vec<&str> = (0..num_columns)
+        .map(|col_i| match table.alignment(col_i) {
+            Alignment::None => "1",
+            Alignment::Left => "<",
+            Alignment::Center => "^",
+            Alignment::Right => ">",
+        })
+        .collect();
+
+    let mut output = format!("[cols=\"{}\",options=\"header\"]\n|===\n", cols.join(","));
+    for (row_i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(col_i, cell)| {
+                if col_i + 1 == num_columns {
+                    format!("| {cell}")
+                } else {
+                    format!("| {}", pad_cell(cell, widths[col_i], Alignment::None))
+                }
+            })
+            .collect();
+        output.push_str(cells.join(" ").trim_end());
+        output.push('\n');
+        if row_i == 0 && rows.len() > 1 {
+            output.push('\n');
+        }
+    }
+    output.push_str("|===\n");
+
+    output
+}
+
+/// Escapes pipes and turns line breaks into AsciiDoc hard breaks.
+fn escape_cell(cell: &str) -> String {
+    cell_lines(cell)
+        .iter()
+        .map(|line| line.replace('|', "\\|"))
+        .collect::<Vec<_>>()
+        .join(" +\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes_pipes_and_line_breaks() {
+        let table = Table::new(
+            vec!["a".to_string(), "b".to_string()],
+            vec![vec!["x|y".to_string(), "one<br>two".to_string()]],
+        );
+
+        assert_eq!(
+            write_table(&table),
+            "[cols=\"1,1\",options=\"header\"]\n|===\n| a    | b\n\n| x\\|y | one +\ntwo\n|===\n"
+        );
+    }
+}
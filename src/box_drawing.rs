@@ -0,0 +1,238 @@
+//! Rendering of tables with box-drawing characters for reading in a terminal.
+//!
+//! ```text
+//! ┌──────┬─────┐
+//! │ Name │ Qty │
+//! ├──────┼─────┤
+//! │ tea  │   1 │
+//! └──────┴─────┘
+//! ```
+
+use crate::column_widths;
+use crate::table::{cell_lines, pad_cell_as};
+use crate::{Table, WidthPolicy};
+
+/// Border characters used to draw a table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// Light lines: `┌─┬─┐`
+    #[default]
+    Single,
+    /// Double lines: `╔═╦═╗`
+    Double,
+    /// Light lines with rounded corners: `╭─┬─╮`
+    Rounded,
+    /// Plain ASCII: `+-+-+`
+    Ascii,
+    /// Light lines with a heavy rule under the header: `┝━┿━┥`
+    Heavy,
+}
+
+/// Options controlling box-drawing output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoxOptions {
+    /// Characters used for the borders.
+    pub style: BoxStyle,
+
+    /// How to measure the width of cell text.
+    pub width_policy: WidthPolicy,
+}
+
+/// The pieces of one horizontal rule: left end, fill, junction, right end.
+type Rule = [char; 4];
+
+/// The rules and vertical line of a style.
+struct Borders {
+    top: Rule,
+    header: Rule,
+    bottom: Rule,
+    vertical: char,
+}
+
+impl BoxStyle {
+    fn borders(self) -> Borders {
+        match self {
+            Self::Single => Borders {
+                top: ['┌', '─', '┬', '┐'],
+                header: ['├', '─', '┼', '┤'],
+                bottom: ['└', '─', '┴', '┘'],
+                vertical: '│',
+            },
+            Self::Double => Borders {
+                top: ['╔', '═', '╦', '╗'],
+                header: ['╠', '═', '╬', '╣'],
+                bottom: ['╚', '═', '╩', '╝'],
+                vertical: '║',
+            },
+            Self::Rounded => Borders {
+                top: ['╭', '─', '┬', '╮'],
+                header: ['├', '─', '┼', '┤'],
+                bottom: ['╰', '─', '┴', '╯'],
+                vertical: '│',
+            },
+            Self::Ascii => Borders {
+                top: ['+', '-', '+', '+'],
+                header: ['+', '-', '+', '+'],
+                bottom: ['+', '-', '+', '+'],
+                vertical: '|',
+            },
+            Self::Heavy => Borders {
+                top: ['┌', '─', '┬', '┐'],
+                header: ['┝', '━', '┿', '┥'],
+                bottom: ['└', '─', '┴', '┘'],
+                vertical: '│',
+            },
+        }
+    }
+}
+
+/// Renders a table with box-drawing borders.
+///
+/// Columns are sized with the same display-width calculation as the
+/// Markdown formatter and cells are padded according to column alignment.
+/// Line breaks and `<br>` tags in cells become separate lines. A table whose
+/// header is entirely empty is drawn without a header row.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::box_drawing::{write_table, BoxOptions, BoxStyle};
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Name | Qty |\n|-|-:|\n| tea | 1 |")
+///     .unwrap();
+/// let options = BoxOptions {
+///     style: BoxStyle::Ascii,
+///     ..BoxOptions::default()
+/// };
+/// assert_eq!(
+///     write_table(&table, &options),
+///     "+------+-----+\n| Name | Qty |\n+------+-----+\n| tea  |   1 |\n+------+-----+\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table, options: &BoxOptions) -> String {
+    let borders = options.style.borders();
+    let num_columns = table.column_count();
+    let has_header = table.header.iter().any(|cell| !cell.trim().is_empty());
+
+    // Each row is split into the physical lines it occupies
+    let rows: Vec<Vec<Vec<&str>>> = std::iter::once(&table.header)
+        .filter(|_| has_header)
+        .chain(&table.rows)
+        .map(|row| {
+            let cells: Vec<Vec<&str>> = (0..num_columns)
+                .map(|col_i| cell_lines(row.get(col_i).map_or("", String::as_str)))
+                .collect();
+            let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+            (0..height)
+                .map(|line_i| {
+                    cells
+                        .iter()
+                        .map(|lines| lines.get(line_i).copied().unwrap_or(""))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    let widths = column_widths(&rows.concat(), options.width_policy);
+
+    let mut output = rule(&widths, borders.top);
+    for (row_i, row) in rows.iter().enumerate() {
+        for line in row {
+            output.push(borders.vertical);
+            for (col_i, text) in line.iter().enumerate() {
+                output.push(' ');
+                output.push_str(&pad_cell_as(
+                    text,
+                    widths[col_i],
+                    table.alignment(col_i),
+                    options.width_policy,
+                ));
+                output.push(' ');
+                output.push(borders.vertical);
+            }
+            output.push('\n');
+        }
+        if row_i == 0 && has_header && rows.len() > 1 {
+            output.push_str(&rule(&widths, borders.header));
+        }
+    }
+    output.push_str(&rule(&widths, borders.bottom));
+
+    output
+}
+
+/// Draws one horizontal rule across all columns.
+fn rule(widths: &[usize], [left, fill, junction, right]: Rule) -> String {
+    let mut line = String::new();
+    line.push(left);
+    for (col_i, &width) in widths.iter().enumerate() {
+        if col_i > 0 {
+            line.push(junction);
+        }
+        line.extend(std::iter::repeat_n(fill, width + 2));
+    }
+    line.push(right);
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Alignment;
+
+    fn sample() -> Table {
+        let mut table = Table::new(
+            vec!["名前".to_string(), "n".to_string()],
+            vec![vec!["a<br>bb".to_string(), "7".to_string()]],
+        );
+        table.alignments = vec![Alignment::Center, Alignment::Right];
+        table
+    }
+
+    #[test]
+    fn test_single_style_with_wide_characters_and_line_breaks() {
+        assert_eq!(
+            write_table(&sample(), &BoxOptions::default()),
+            "┌──────┬───┐\n│ 名前 │ n │\n├──────┼───┤\n│  a   │ 7 │\n│  bb  │   │\n└──────┴───┘\n"
+        );
+    }
+
+    #[test]
+    fn test_heavy_header_rule() {
+        let options = BoxOptions {
+            style: BoxStyle::Heavy,
+            ..BoxOptions::default()
+        };
+
+        assert!(write_table(&sample(), &options).contains("┝━━━━━━┿━━━┥\n"));
+    }
+
+    #[test]
+    fn test_cjk_width_policy() {
+        let table = Table::new(vec!["±°".to_string()], vec![vec!["ab".to_string()]]);
+        let options = BoxOptions {
+            style: BoxStyle::Ascii,
+            width_policy: WidthPolicy::Cjk,
+        };
+
+        assert_eq!(
+            write_table(&table, &options),
+            "+------+\n| ±° |\n+------+\n| ab   |\n+------+\n"
+        );
+    }
+
+    #[test]
+    fn test_headerless_table_has_no_header_rule() {
+        let table = Table::new(vec![String::new()], vec![vec!["x".to_string()]]);
+        let options = BoxOptions {
+            style: BoxStyle::Double,
+            ..BoxOptions::default()
+        };
+
+        assert_eq!(write_table(&table, &options), "╔═══╗\n║ x ║\n╚═══╝\n");
+    }
+}
@@ -0,0 +1,262 @@
+//! Import of whitespace-aligned text, such as the output of `ps`,
+//! `kubectl get` or `docker ps`.
+//!
+//! Column boundaries are inferred from gutters: display columns that are
+//! blank on every line.
+
+use crate::rst::split_simple_line;
+use crate::{Result, Table, TableError};
+use unicode_width::UnicodeWidthChar;
+
+/// Display width of a tab stop when expanding tabs.
+const TAB_WIDTH: usize = 8;
+
+/// Options controlling how aligned text is parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnsOptions {
+    /// Whether the first line holds the column names.
+    pub has_header: bool,
+}
+
+impl Default for ColumnsOptions {
+    fn default() -> Self {
+        Self { has_header: true }
+    }
+}
+
+/// Parses whitespace-aligned text into a [`Table`].
+///
+/// Gutters two or more columns wide always separate columns. A single blank
+/// column only does when the header has words on both sides of it, as in
+/// `PID TTY`, so values such as `2 hours ago` stay in one cell. Display
+/// width is used throughout, so CJK text and emoji line up, and tabs are
+/// expanded to 8-column stops. Blank lines are skipped. Without a header
+/// line, columns are named `Column 1`, `Column 2`, and so on.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there are no non-blank lines.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::columns::{parse_table, ColumnsOptions};
+///
+/// let input = "\
+/// NAME      READY   STATUS    AGE
+/// web-1     1/1     Running   2 days ago
+/// worker-2  0/1     Pending   5 mins ago
+/// ";
+/// let table = parse_table(input, &ColumnsOptions::default()).unwrap();
+/// assert_eq!(table.header, vec!["NAME", "READY", "STATUS", "AGE"]);
+/// assert_eq!(table.rows[0], vec!["web-1", "1/1", "Running", "2 days ago"]);
+/// ```
+pub fn parse_table(input: &str, options: &ColumnsOptions) -> Result<Table> {
+    // Keep the 1-based line numbers for error messages
+    let (line_numbers, lines): (Vec<usize>, Vec<String>) = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, expand_tabs(line)))
+        .unzip();
+    if lines.is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+
+    let header_line = options.has_header.then(|| lines[0].as_str());
+    let columns = infer_columns(&lines, header_line);
+
+    let mut rows: Vec<Vec<String>> = lines
+        .iter()
+        .zip(&line_numbers)
+        .map(|(line, number)| {
+            split_simple_line(line, &columns).ok_or_else(|| {
+                TableError::InvalidStructure(format!(
+                    "Line {number} does not fit the {} columns found: {}",
+                    columns.len(),
+                    line.trim()
+                ))
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let header = if options.has_header {
+        rows.remove(0)
+    } else {
+        (1..=columns.len()).map(|i| format!("Column {i}")).collect()
+    };
+
+    Ok(Table::new(header, rows))
+}
+
+/// Converts whitespace-aligned text into an aligned Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if the input cannot be parsed or formatted.
+pub fn to_markdown(input: &str, options: &ColumnsOptions) -> Result<String> {
+    parse_table(input, options)?.to_markdown()
+}
+
+/// Finds the (start, end) display columns of each column of text.
+fn infer_columns(lines: &[String], header: Option<&str>) -> Vec<(usize, usize)> {
+    let occupied = occupied_columns(lines.iter().map(String::as_str));
+    let header_occupied = header.map(|header| occupied_columns(std::iter::once(header)));
+    let is_header_word = |column: usize| {
+        header_occupied
+            .as_ref()
+            .is_some_and(|occupied| occupied.get(column).copied().unwrap_or(false))
+    };
+
+    let mut columns: Vec<(usize, usize)> = Vec::new();
+    let mut start: Option<usize> = None;
+    for (column, &filled) in occupied.iter().enumerate() {
+        match (filled, start) {
+            (true, None) => {
+                // A one-column gutter only splits words that the header splits too
+                let joins_previous = columns.last().is_some_and(|&(_, end)| {
+                    column == end + 1 && !(is_header_word(end - 1) && is_header_word(column))
+                });
+                start = if joins_previous {
+                    columns.pop().map(|(previous_start, _)| previous_start)
+                } else {
+                    Some(column)
+                };
+            }
+            (false, Some(run_start)) => {
+                columns.push((run_start, column));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(run_start) = start {
+        columns.push((run_start, occupied.len()));
+    }
+
+    columns
+}
+
+/// Marks the display columns that hold a non-blank character on any line.
+fn occupied_columns<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<bool> {
+    let mut occupied = Vec::new();
+
+    for line in lines {
+        let mut column = 0;
+        for c in line.chars() {
+            let width = c.width().unwrap_or(0);
+            if !c.is_whitespace() {
+                let end = column + width.max(1);
+                if occupied.len() < end {
+                    occupied.resize(end, false);
+                }
+                occupied[column..end].fill(true);
+            }
+            column += width;
+        }
+    }
+
+    occupied
+}
+
+/// Replaces tabs with spaces up to the next tab stop.
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += c.width().unwrap_or(0);
+        }
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ps_output_with_single_space_gutter() {
+        let input = concat!(
+            "    PID TTY          TIME CMD\n",
+            "   4821 pts/0    00:00:01 bash\n",
+            "  12007 pts/0    00:00:00 ps -ef --forest\n",
+        );
+        let table = parse_table(input, &ColumnsOptions::default()).expect("Should parse");
+
+        assert_eq!(table.header, vec!["PID", "TTY", "TIME", "CMD"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["4821", "pts/0", "00:00:01", "bash"],
+                vec!["12007", "pts/0", "00:00:00", "ps -ef --forest"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_header_words_with_spaces() {
+        let input = "\
+CONTAINER ID   IMAGE    STATUS
+a1b2c3d4e5f6   nginx    Up 2 hours
+0f9e8d7c6b5a   redis    Exited (0) 3 days ago
+";
+        let table = parse_table(input, &ColumnsOptions::default()).expect("Should parse");
+
+        assert_eq!(table.header, vec!["CONTAINER ID", "IMAGE", "STATUS"]);
+        assert_eq!(
+            table.rows[1],
+            vec!["0f9e8d7c6b5a", "redis", "Exited (0) 3 days ago"]
+        );
+    }
+
+    #[test]
+    fn test_wide_characters_and_tabs() {
+        let input = "名前\tqty\n茶\t1\nkiwi\t22\n";
+        let table = parse_table(input, &ColumnsOptions::default()).expect("Should parse");
+
+        assert_eq!(table.header, vec!["名前", "qty"]);
+        assert_eq!(table.rows, vec![vec!["茶", "1"], vec!["kiwi", "22"]]);
+    }
+
+    #[test]
+    fn test_without_header() {
+        let options = ColumnsOptions { has_header: false };
+        let table = parse_table("a  1\nb  2\n", &options).expect("Should parse");
+
+        assert_eq!(table.header, vec!["Column 1", "Column 2"]);
+        assert_eq!(table.rows, vec![vec!["a", "1"], vec!["b", "2"]]);
+    }
+
+    #[test]
+    fn test_empty_input_error() {
+        let result = parse_table("\n  \n", &ColumnsOptions::default());
+
+        assert!(matches!(result, Err(TableError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_line_with_text_in_gutter_error() {
+        // A no-break space leaves its column blank, yet counts as text in the gutter
+        let result = parse_table(
+            "a   b
+1 \u{a0} 2
+",
+            &ColumnsOptions::default(),
+        );
+
+        assert_eq!(
+            result,
+            Err(TableError::InvalidStructure(
+                "Line 2 does not fit the 2 columns found: 1 \u{a0} 2".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Project configuration, read from `.ftb.toml` or from the `[tool.ftb]`
+//! table of `pyproject.toml`.
+//!
+//! ```toml
+//! [files]
+//! extensions = ["md", "markdown"]
+//! exclude = ["docs/generated/**"]
+//!
+//! [format]
+//! width = "cjk"
+//! box-style = "rounded"
+//!
+//! [[overrides]]
+//! paths = ["legacy/**/*.txt"]
+//! format = { dialect = "rst" }
+//! ```
+
+use crate::files::{self, has_extension};
+use crate::{BoxStyleArg, DialectArg, WidthArg};
+use globset::{GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Name of the configuration file looked for in each directory.
+const CONFIG_FILE: &str = ".ftb.toml";
+
+/// Python project file whose `[tool.ftb]` table is used when there is no `.ftb.toml`.
+const PYPROJECT_FILE: &str = "pyproject.toml";
+
+/// Extensions of the files formatted when walking directories, unless configured.
+pub const DEFAULT_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+/// The contents of a configuration file.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Which files to pick up when walking directories.
+    pub files: FileSettings,
+
+    /// How to format documents.
+    pub format: FormatSettings,
+
+    /// Format settings for paths matching some globs, applied in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<Override>,
+}
+
+/// Settings selecting the files found when walking directories.
+///
+/// Files named on the command line are always formatted.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileSettings {
+    /// Extensions of the files to format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<String>>,
+
+    /// Globs of the files to format, used instead of `extensions` when set.
+    pub include: Vec<String>,
+
+    /// Globs of files never to format.
+    pub exclude: Vec<String>,
+}
+
+/// Settings controlling how a document is formatted or rendered.
+///
+/// Every setting is optional so that command-line flags, overrides and the
+/// base configuration can be layered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FormatSettings {
+    /// Markup language of the documents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<DialectArg>,
+
+    /// How to measure characters whose width depends on the font.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<WidthArg>,
+
+    /// Border style for box-drawing output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub box_style: Option<BoxStyleArg>,
+
+    /// Use inline text-align styles in HTML output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html_inline_styles: Option<bool>,
+
+    /// Use booktabs rules in LaTeX output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latex_booktabs: Option<bool>,
+
+    /// Convert JSON values based on inferred column types.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coerce_types: Option<bool>,
+}
+
+impl FormatSettings {
+    /// Keeps the settings made here and takes the rest from `fallback`.
+    #[must_use]
+    pub fn or(self, fallback: FormatSettings) -> FormatSettings {
+        FormatSettings {
+            dialect: self.dialect.or(fallback.dialect),
+            width: self.width.or(fallback.width),
+            box_style: self.box_style.or(fallback.box_style),
+            html_inline_styles: self.html_inline_styles.or(fallback.html_inline_styles),
+            latex_booktabs: self.latex_booktabs.or(fallback.latex_booktabs),
+            coerce_types: self.coerce_types.or(fallback.coerce_types),
+        }
+    }
+}
+
+/// Format settings for the paths matching some globs.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    /// Globs, relative to the configuration file, of the paths to apply to.
+    pub paths: Vec<String>,
+
+    /// Settings replacing those of the base configuration.
+    #[serde(default)]
+    pub format: FormatSettings,
+}
+
+/// A loaded configuration with its globs compiled.
+#[derive(Debug, Default)]
+pub struct ProjectConfig {
+    /// The file the configuration was read from, or `None` for defaults.
+    pub source: Option<PathBuf>,
+
+    /// The configuration as written.
+    pub config: Config,
+
+    /// Directory that globs are relative to.
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    overrides: Vec<GlobSet>,
+}
+
+impl ProjectConfig {
+    /// Compiles the globs of a configuration found in `root`.
+    fn new(source: PathBuf, root: PathBuf, config: Config) -> Result<Self, String> {
+        let include =
+            (!config.files.include.is_empty()).then(|| glob_set(&config.files.include, &source));
+        let exclude = glob_set(&config.files.exclude, &source)?;
+        let overrides = config
+            .overrides
+            .iter()
+            .map(|entry| glob_set(&entry.paths, &source))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            source: Some(source),
+            config,
+            root,
+            include: include.transpose()?,
+            exclude,
+            overrides,
+        })
+    }
+
+    /// Returns the format settings for a file, with matching overrides applied.
+    pub fn format_settings(&self, path: &Path) -> FormatSettings {
+        let relative = self.relative(path);
+
+        self.config
+            .overrides
+            .iter()
+            .zip(&self.overrides)
+            .filter(|(_, globs)| globs.is_match(&relative))
+            .fold(self.config.format.clone(), |settings, (entry, _)| {
+                entry.format.clone().or(settings)
+            })
+    }
+
+    /// Returns the extensions of the files to format when walking directories.
+    pub fn extensions(&self) -> Vec<String> {
+        self.config
+            .files
+            .extensions
+            .clone()
+            .unwrap_or_else(|| DEFAULT_EXTENSIONS.iter().map(ToString::to_string).collect())
+    }
+
+    /// Decides whether a file found by walking a directory should be formatted.
+    ///
+    /// `extensions` from the command line take precedence over the configured ones.
+    pub fn selects(&self, path: &Path, extensions: Option<&[String]>) -> bool {
+        let relative = self.relative(path);
+        if self.exclude.is_match(&relative) {
+            return false;
+        }
+
+        match (extensions, &self.include) {
+            (Some(extensions), _) => has_extension(path, extensions),
+            (None, Some(include)) => include.is_match(&relative),
+            (None, None) => has_extension(path, &self.extensions()),
+        }
+    }
+
+    /// Makes a path relative to the configuration directory for glob matching.
+    fn relative(&self, path: &Path) -> PathBuf {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        path.strip_prefix(&self.root)
+            .map_or_else(|_| path.clone(), Path::to_path_buf)
+    }
+}
+
+/// Finds and caches the configuration that applies to each directory.
+#[derive(Debug, Default)]
+pub struct ConfigCache {
+    by_dir: HashMap<PathBuf, Rc<ProjectConfig>>,
+}
+
+impl ConfigCache {
+    /// Returns the configuration for a file or directory, found by walking up
+    /// from it to the nearest `.ftb.toml` or `pyproject.toml` with a
+    /// `[tool.ftb]` table. Defaults are used when there is none.
+    pub fn for_path(&mut self, path: &Path) -> Result<Rc<ProjectConfig>, String> {
+        let path = fs::canonicalize(path)
+            .map_err(|e| format!("Cannot access file: {}\nError: {e}", path.display()))?;
+        let dir = if path.is_dir() {
+            path.as_path()
+        } else {
+            path.parent().unwrap_or(&path)
+        };
+
+        self.for_dir(dir)
+    }
+
+    /// Returns the configuration for a canonical directory path.
+    fn for_dir(&mut self, dir: &Path) -> Result<Rc<ProjectConfig>, String> {
+        if let Some(config) = self.by_dir.get(dir) {
+            return Ok(Rc::clone(config));
+        }
+
+        let config = match load(dir)? {
+            Some(config) => Rc::new(config),
+            None => match dir.parent() {
+                Some(parent) => self.for_dir(parent)?,
+                None => Rc::new(ProjectConfig::default()),
+            },
+        };
+        self.by_dir.insert(dir.to_path_buf(), Rc::clone(&config));
+
+        Ok(config)
+    }
+}
+
+/// Reads the configuration stored in a directory, if any.
+fn load(dir: &Path) -> Result<Option<ProjectConfig>, String> {
+    let path = dir.join(CONFIG_FILE);
+    if path.is_file() {
+        let config = toml::from_str(&read(&path)?).map_err(|e| invalid(&path, &e))?;
+        return ProjectConfig::new(path, dir.to_path_buf(), config).map(Some);
+    }
+
+    let path = dir.join(PYPROJECT_FILE);
+    if path.is_file() {
+        let pyproject: toml::Table =
+            toml::from_str(&read(&path)?).map_err(|e| invalid(&path, &e))?;
+        let section = pyproject
+            .get("tool")
+            .and_then(|tool| tool.get("ftb"))
+            .cloned();
+        if let Some(section) = section {
+            let config = section.try_into().map_err(|e| invalid(&path, &e))?;
+            return ProjectConfig::new(path, dir.to_path_buf(), config).map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reads a configuration file.
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read file: {}\nError: {e}", path.display()))
+}
+
+/// Describes an error in a configuration file.
+fn invalid(path: &Path, error: &dyn std::fmt::Display) -> String {
+    // TOML parse errors end with a newline after the source excerpt
+    let error = error.to_string();
+    format!(
+        "Invalid configuration in {}: {}",
+        path.display(),
+        error.trim_end()
+    )
+}
+
+/// Compiles globs in which `*` stops at `/` and `**` crosses directories.
+fn glob_set(patterns: &[String], source: &Path) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(files::glob(pattern).map_err(|e| invalid(source, &e))?);
+    }
+
+    builder.build().map_err(|e| invalid(source, &e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::temp_dir;
+
+    #[test]
+    fn test_discovers_config_in_parent_directory() {
+        let dir = temp_dir("config-discover");
+        fs::create_dir_all(dir.join("docs/legacy")).unwrap();
+        fs::write(
+            dir.join(CONFIG_FILE),
+            r#"
+[format]
+width = "cjk"
+box-style = "rounded"
+
+[[overrides]]
+paths = ["docs/legacy/**"]
+format = { dialect = "rst", box-style = "ascii" }
+"#,
+        )
+        .unwrap();
+        fs::write(dir.join("docs/guide.md"), "").unwrap();
+        fs::write(dir.join("docs/legacy/old.txt"), "").unwrap();
+
+        let mut cache = ConfigCache::default();
+        let config = cache.for_path(&dir.join("docs/guide.md")).unwrap();
+        assert_eq!(
+            config.source.as_deref(),
+            Some(dir.join(CONFIG_FILE).as_path())
+        );
+
+        let guide = config.format_settings(&dir.join("docs/guide.md"));
+        assert_eq!(guide.width, Some(WidthArg::Cjk));
+        assert_eq!(guide.box_style, Some(BoxStyleArg::Rounded));
+        assert_eq!(guide.dialect, None);
+
+        let old = config.format_settings(&dir.join("docs/legacy/old.txt"));
+        assert_eq!(old.dialect, Some(DialectArg::Rst));
+        assert_eq!(old.box_style, Some(BoxStyleArg::Ascii));
+        assert_eq!(old.width, Some(WidthArg::Cjk));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pyproject_section_and_file_selection() {
+        let dir = temp_dir("config-pyproject");
+        fs::write(
+            dir.join(PYPROJECT_FILE),
+            "[project]\nname = \"demo\"\n\n[tool.ftb.files]\nextensions = [\"md\", \"rst\"]\nexclude = [\"build/**\"]\n",
+        )
+        .unwrap();
+
+        let config = ConfigCache::default().for_path(&dir).unwrap();
+        assert!(config.selects(&dir.join("index.rst"), None));
+        assert!(!config.selects(&dir.join("notes.txt"), None));
+        assert!(!config.selects(&dir.join("build/out.md"), None));
+
+        let txt = vec!["txt".to_string()];
+        assert!(config.selects(&dir.join("notes.txt"), Some(&txt)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let dir = temp_dir("config-invalid");
+        fs::write(dir.join(CONFIG_FILE), "[format]\nbox_style = \"double\"\n").unwrap();
+
+        let error = ConfigCache::default().for_path(&dir).unwrap_err();
+        assert!(error.contains("unknown field `box_style`"), "{error}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
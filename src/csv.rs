@@ -0,0 +1,272 @@
+//! Import and export of CSV and TSV data.
+//!
+//! Parsing follows RFC 4180: fields may be wrapped in double quotes, quotes
+//! inside a quoted field are doubled (`""`), and quoted fields may contain the
+//! delimiter and line breaks. Both `\n` and `\r\n` record terminators are
+//! accepted.
+
+use crate::{Result, Table, TableError};
+
+/// Options controlling how delimited text is parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field delimiter, `,` for CSV and `\t` for TSV.
+    pub delimiter: char,
+
+    /// Whether the first record holds the column names.
+    pub has_header: bool,
+}
+
+impl CsvOptions {
+    /// Options for comma-separated values with a header record.
+    #[must_use]
+    pub fn csv() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: true,
+        }
+    }
+
+    /// Options for tab-separated values with a header record.
+    #[must_use]
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: '\t',
+            has_header: true,
+        }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self::csv()
+    }
+}
+
+/// Parses delimited text into records of fields.
+///
+/// Blank lines between records are skipped and a leading byte order mark is
+/// ignored.
+///
+/// # Errors
+///
+/// Returns `TableError::InvalidStructure` if a quoted field is never closed.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::csv::{parse_records, CsvOptions};
+///
+/// let records = parse_records("a,\"b, c\"\n1,\"say \"\"hi\"\"\"\n", &CsvOptions::csv()).unwrap();
+/// assert_eq!(records, vec![vec!["a", "b, c"], vec!["1", "say \"hi\""]]);
+/// ```
+pub fn parse_records(input: &str, options: &CsvOptions) -> Result<Vec<Vec<String>>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut quote_line = 0;
+    let mut line = 1;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                end_record(&mut records, &mut record, &mut field);
+            }
+            _ if c == options.delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(TableError::InvalidStructure(format!(
+            "Unterminated quoted field starting on line {quote_line}"
+        )));
+    }
+    end_record(&mut records, &mut record, &mut field);
+
+    Ok(records)
+}
+
+/// Finishes the current record, dropping it if the line was blank.
+fn end_record(records: &mut Vec<Vec<String>>, record: &mut Vec<String>, field: &mut String) {
+    if record.is_empty() && field.is_empty() {
+        return;
+    }
+    record.push(std::mem::take(field));
+    records.push(std::mem::take(record));
+}
+
+/// Parses delimited text into a [`Table`].
+///
+/// Without a header record, columns are named `Column 1`, `Column 2`, and so on.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there are no records, or
+/// `TableError::InvalidStructure` if the input is malformed.
+pub fn parse_table(input: &str, options: &CsvOptions) -> Result<Table> {
+    let mut records = parse_records(input, options)?.into_iter();
+
+    if options.has_header {
+        let header = records.next().ok_or(TableError::EmptyInput)?;
+        return Ok(Table::new(header, records.collect()));
+    }
+
+    let rows: Vec<Vec<String>> = records.collect();
+    if rows.is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+    let num_columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let header = (1..=num_columns).map(|i| format!("Column {i}")).collect();
+
+    Ok(Table::new(header, rows))
+}
+
+/// Converts delimited text into an aligned Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if the input cannot be parsed or formatted.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::csv::{to_markdown, CsvOptions};
+///
+/// let output = to_markdown("name,qty\napple,3\n", &CsvOptions::csv()).unwrap();
+/// assert_eq!(output, "| name  | qty |\n|-------|-----|\n| apple | 3   |\n");
+/// ```
+pub fn to_markdown(input: &str, options: &CsvOptions) -> Result<String> {
+    parse_table(input, options)?.to_markdown()
+}
+
+/// Writes a [`Table`] as delimited text, header record first.
+///
+/// Fields containing the delimiter, a double quote or a line break are
+/// quoted, with embedded quotes doubled. Records end with `\n`.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::Table;
+///
+/// let table = Table::new(
+///     vec!["name".to_string(), "note".to_string()],
+///     vec![vec!["Doe, Jane".to_string(), "says \"hi\"".to_string()]],
+/// );
+/// let csv = ftb::csv::write_table(&table, ',');
+/// assert_eq!(csv, "name,note\n\"Doe, Jane\",\"says \"\"hi\"\"\"\n");
+/// ```
+#[must_use]
+pub fn write_table(table: &Table, delimiter: char) -> String {
+    let mut output = String::new();
+
+    for record in std::iter::once(&table.header).chain(&table.rows) {
+        for (i, field) in record.iter().enumerate() {
+            if i > 0 {
+                output.push(delimiter);
+            }
+            push_field(&mut output, field, delimiter);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Appends a single field, quoting it when required.
+fn push_field(output: &mut String, field: &str, delimiter: char) {
+    let needs_quotes = field
+        .chars()
+        .any(|c| c == delimiter || c == '"' || c == '\n' || c == '\r');
+
+    if needs_quotes {
+        output.push('"');
+        output.push_str(&field.replace('"', "\"\""));
+        output.push('"');
+    } else {
+        output.push_str(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_fields_with_delimiters_and_newlines() {
+        let input = "a,b\n\"1,5\",\"two\nlines\"\r\n";
+        let records = parse_records(input, &CsvOptions::csv()).expect("Should parse");
+
+        assert_eq!(records, vec![vec!["a", "b"], vec!["1,5", "two\nlines"]]);
+    }
+
+    #[test]
+    fn test_empty_fields_are_kept() {
+        let records = parse_records(",x,\n", &CsvOptions::csv()).expect("Should parse");
+
+        assert_eq!(records, vec![vec!["", "x", ""]]);
+    }
+
+    #[test]
+    fn test_unterminated_quote_error() {
+        let result = parse_records("a,b\n1,\"oops\n", &CsvOptions::csv());
+
+        assert!(matches!(result, Err(TableError::InvalidStructure(_))));
+    }
+
+    #[test]
+    fn test_tsv_without_header() {
+        let options = CsvOptions {
+            has_header: false,
+            ..CsvOptions::tsv()
+        };
+        let output = to_markdown("x\t1\ny\t2\n", &options).expect("Should convert");
+
+        assert_eq!(
+            output,
+            "| Column 1 | Column 2 |\n|----------|----------|\n| x        | 1        |\n| y        | 2        |\n"
+        );
+    }
+
+    #[test]
+    fn test_write_table_round_trip() {
+        let input = "a,b\n\"x, y\",\"two\nlines\"\n";
+        let table = parse_table(input, &CsvOptions::csv()).expect("Should parse");
+
+        assert_eq!(write_table(&table, ','), input);
+    }
+
+    #[test]
+    fn test_empty_input_error() {
+        let result = to_markdown("\n\n", &CsvOptions::csv());
+
+        assert!(matches!(result, Err(TableError::EmptyInput)));
+    }
+}
@@ -0,0 +1,345 @@
+//! Tables that could not be formatted: where they are, what is wrong with
+//! them, and how to show that to a person.
+
+use crate::{line_starts, split_row, Alignment, TableError, TextPosition};
+use std::fmt;
+use std::ops::Range;
+use unicode_width::UnicodeWidthStr;
+
+/// Spans longer than this many lines are shown with the middle elided.
+const MAX_SHOWN_LINES: usize = 5;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+    /// The table was left as it is and formatting went on.
+    #[default]
+    Warning,
+
+    /// The problem should fail the run, as with `--strict`.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The part of a document a diagnostic points at.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// The byte range covered.
+    pub range: Range<usize>,
+
+    /// Where the span starts.
+    pub start: TextPosition,
+
+    /// Where the span ends.
+    pub end: TextPosition,
+}
+
+/// The part of a table an error is about: a line of the table (0-based) and a
+/// byte range within that line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ErrorLocation {
+    pub(crate) line: usize,
+    pub(crate) columns: Range<usize>,
+}
+
+/// An error raised while reading a table, with the place it is about when it
+/// is about one place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LocatedError {
+    pub(crate) error: TableError,
+    pub(crate) location: Option<ErrorLocation>,
+}
+
+impl LocatedError {
+    /// Locates an error at the text of line `line` of a table, `text` being
+    /// that line.
+    pub(crate) fn at_line(error: TableError, line: usize, text: &str) -> Self {
+        let start = text.len() - text.trim_start().len();
+        let end = text.trim_end().len().max(start);
+        Self {
+            error,
+            location: Some(ErrorLocation {
+                line,
+                columns: start..end,
+            }),
+        }
+    }
+
+    /// Locates an error at the first cell of a Markdown separator row that is
+    /// not made of dashes, `text` being line `line` of the table.
+    pub(crate) fn at_separator(error: TableError, line: usize, text: &str) -> Self {
+        Self {
+            error,
+            location: bad_separator_cell(text).map(|columns| ErrorLocation { line, columns }),
+        }
+    }
+}
+
+impl From<TableError> for LocatedError {
+    fn from(error: TableError) -> Self {
+        Self {
+            error,
+            location: None,
+        }
+    }
+}
+
+impl From<LocatedError> for TableError {
+    fn from(located: LocatedError) -> Self {
+        located.error
+    }
+}
+
+/// A table that could not be formatted and was left as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDiagnostic {
+    /// Index of the first line of the table (0-based).
+    pub start_line: usize,
+
+    /// Index one past the last line of the table.
+    pub end_line: usize,
+
+    /// Why the table could not be formatted.
+    pub error: TableError,
+
+    /// How serious the problem is.
+    pub severity: Severity,
+
+    /// The offending part of the table, such as the first cell of a
+    /// separator row that is not made of dashes, or the whole table when the
+    /// error is not about one place.
+    pub span: SourceSpan,
+}
+
+impl TableDiagnostic {
+    /// Creates a warning about the table on lines `start_line..end_line`.
+    ///
+    /// The span is left empty until the scan is over and the lines of the
+    /// table are final; see [`locate`].
+    pub(crate) fn new(start_line: usize, end_line: usize, error: TableError) -> Self {
+        Self {
+            start_line,
+            end_line,
+            error,
+            severity: Severity::Warning,
+            span: SourceSpan::default(),
+        }
+    }
+
+    /// Renders the diagnostic for a person, showing the offending lines of
+    /// `document` with carets under the span, in the style of rustc.
+    ///
+    /// `name` identifies the document, usually its path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableFormatter};
+    ///
+    /// let doc = "| a | b |\n| 1 | 2 |\n";
+    /// let diagnostics = TableFormatter::new().diagnose_document_as(doc, Dialect::Markdown);
+    /// assert_eq!(
+    ///     diagnostics[0].render(doc, "notes.md"),
+    ///     "warning: Invalid table structure: Row 2 is not a valid separator row\n \
+    ///      --> notes.md:2:3\n  \
+    ///       |\n\
+    ///      1 | | a | b |\n\
+    ///      2 | | 1 | 2 |\n  \
+    ///       |   ^\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render(&self, document: &str, name: &str) -> String {
+        let lines: Vec<&str> = document.lines().collect();
+        let (start, end) = (self.span.start, self.span.end);
+        // Show the header above a bad row, as context
+        let first = if start.line == end.line {
+            start.line.saturating_sub(1).max(self.start_line)
+        } else {
+            start.line
+        };
+        let last = end.line.min(lines.len().saturating_sub(1));
+        let gutter = (last + 1).to_string().len();
+        let column = lines
+            .get(start.line)
+            .and_then(|line| line.get(..start.utf8_column))
+            .map_or(0, |before| before.chars().count());
+
+        let mut output = format!("{}: {}\n", self.severity, self.error);
+        output.push_str(&format!(
+            "{:gutter$}--> {name}:{}:{}\n",
+            "",
+            start.line + 1,
+            column + 1
+        ));
+        output.push_str(&format!("{:gutter$} |\n", ""));
+
+        let shown = last.saturating_sub(first) + 1;
+        for line_i in first..=last {
+            // Elide the middle of a long span, keeping two lines at each end
+            if shown > MAX_SHOWN_LINES && line_i >= first + 2 && line_i + 2 <= last {
+                if line_i == first + 2 {
+                    output.push_str(&format!("{:gutter$} ...\n", ""));
+                }
+                continue;
+            }
+            let text = lines.get(line_i).copied().unwrap_or_default();
+            output.push_str(&format!("{:>gutter$} | {text}\n", line_i + 1));
+        }
+
+        if start.line == end.line {
+            let text = lines.get(start.line).copied().unwrap_or_default();
+            let before = text.get(..start.utf8_column).unwrap_or_default();
+            let marked = text
+                .get(start.utf8_column..end.utf8_column)
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "{:gutter$} | {:indent$}{}\n",
+                "",
+                "",
+                "^".repeat(marked.width().max(1)),
+                indent = before.width()
+            ));
+        }
+
+        output
+    }
+}
+
+/// Fills in the span of each diagnostic of a scanned document from the place
+/// its error was raised, or with the whole table when there is none.
+pub(crate) fn locate(
+    document: &str,
+    diagnostics: &mut [TableDiagnostic],
+    locations: &[Option<ErrorLocation>],
+) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let lines: Vec<&str> = document.lines().collect();
+    let line_starts = line_starts(document);
+    let position = |line: usize, column: usize| {
+        let text = lines.get(line).copied().unwrap_or_default();
+        TextPosition::new(line, &text[..column.min(text.len())])
+    };
+
+    for (diagnostic, location) in diagnostics.iter_mut().zip(locations) {
+        let (start, end) = offending_columns(&lines, diagnostic, location.as_ref());
+        diagnostic.span = SourceSpan {
+            range: line_starts[start.0] + start.1..line_starts[end.0] + end.1,
+            start: position(start.0, start.1),
+            end: position(end.0, end.1),
+        };
+    }
+}
+
+/// Finds the (line, byte column) bounds of the part of a table a diagnostic
+/// is about.
+///
+/// That is the location of the error, unless the table was cut short before
+/// it, and otherwise the text of all the lines of the table.
+fn offending_columns(
+    lines: &[&str],
+    diagnostic: &TableDiagnostic,
+    location: Option<&ErrorLocation>,
+) -> ((usize, usize), (usize, usize)) {
+    let first = diagnostic.start_line;
+    let last = diagnostic.end_line.saturating_sub(1).max(first);
+    let text_bounds = |line: usize| {
+        let text = lines.get(line).copied().unwrap_or_default();
+        let start = text.len() - text.trim_start().len();
+        (start, text.trim_end().len().max(start))
+    };
+
+    if let Some(location) = location.filter(|location| first + location.line <= last) {
+        let line = first + location.line;
+        return ((line, location.columns.start), (line, location.columns.end));
+    }
+
+    ((first, text_bounds(first).0), (last, text_bounds(last).1))
+}
+
+/// Returns the byte range of the first cell of a row that is not a valid
+/// separator cell, trimmed of spaces.
+fn bad_separator_cell(row: &str) -> Option<Range<usize>> {
+    let cells = split_row(row);
+    let count = cells.len();
+    let mut start = 0;
+
+    for (i, cell) in cells.into_iter().enumerate() {
+        let cell_start = start;
+        start += cell.len() + 1;
+        // The empty text outside the outer pipes is not a cell
+        let is_edge = (i == 0 || i + 1 == count) && cell.trim().is_empty();
+        if is_edge || Alignment::from_separator(cell).is_some() {
+            continue;
+        }
+        let leading = cell.len() - cell.trim_start().len();
+        let text_start = cell_start + leading;
+        return Some(text_start..text_start + cell.trim().len());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dialect, TableFormatter};
+
+    fn diagnose(document: &str, dialect: Dialect) -> Vec<TableDiagnostic> {
+        TableFormatter::new().diagnose_document_as(document, dialect)
+    }
+
+    #[test]
+    fn test_span_points_at_bad_separator_cell() {
+        let doc = "Intro\n\n| a | b |\n|---| x y |\n";
+        let diagnostics = diagnose(doc, Dialect::Markdown);
+
+        let span = &diagnostics[0].span;
+        assert_eq!(&doc[span.range.clone()], "x y");
+        assert_eq!((span.start.line, span.start.utf8_column), (3, 6));
+        assert_eq!((span.end.line, span.end.utf8_column), (3, 9));
+    }
+
+    #[test]
+    fn test_span_covers_table_without_separator() {
+        let doc = "| only |\n";
+        let diagnostics = diagnose(doc, Dialect::Markdown);
+        assert_eq!(diagnostics[0].error, TableError::MissingSeparator);
+        assert_eq!(diagnostics[0].span.range, 0..8);
+    }
+
+    #[test]
+    fn test_render_wide_characters_and_long_tables() {
+        let doc = "| 名前 | b |\n| 値 | 2 |\n";
+        let rendered = diagnose(doc, Dialect::Markdown)[0].render(doc, "t.md");
+        assert!(rendered.ends_with("2 | | 値 | 2 |\n  |   ^^\n"));
+
+        // A table without columns is not wrong in any one place
+        let doc = "|---|\n|---|\n|---|\n|---|\n|---|\n|---|\n";
+        let diagnostics = diagnose(doc, Dialect::Org);
+        let rendered = diagnostics[0].render(doc, "t.org");
+        assert!(rendered.contains(" --> t.org:1:1\n"));
+        assert!(rendered.contains("2 | |---|\n  ...\n5 | |---|\n"));
+        assert!(!rendered.contains('^'));
+    }
+
+    #[test]
+    fn test_span_points_at_line_where_error_was_raised() {
+        let doc = "Intro\n\n+---+---+\n| a | b |\n+---+---+\n  | c     |\n+---+---+\n";
+        let diagnostics = diagnose(doc, Dialect::Rst);
+
+        let span = &diagnostics[0].span;
+        assert_eq!(&doc[span.range.clone()], "| c     |");
+        assert_eq!((span.start.line, span.start.utf8_column), (5, 2));
+    }
+}
@@ -0,0 +1,103 @@
+//! Unified diffs between a document and its formatted version.
+
+use similar::TextDiff;
+
+/// Lines of unchanged context shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders a unified diff from `original` to `formatted`, labelled with `name`.
+///
+/// Returns an empty string when the two are identical. With `color`, headers
+/// are bold, hunk ranges cyan, removed lines red and added lines green.
+pub fn unified_diff(original: &str, formatted: &str, name: &str, color: bool) -> String {
+    if original == formatted {
+        return String::new();
+    }
+
+    let diff = TextDiff::from_lines(original, formatted)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(name, name)
+        .to_string();
+
+    if color {
+        colorize(&diff)
+    } else {
+        diff
+    }
+}
+
+/// Adds ANSI colors to the lines of a unified diff.
+fn colorize(diff: &str) -> String {
+    let mut output = String::with_capacity(diff.len() * 2);
+
+    for (i, line) in diff.split_inclusive('\n').enumerate() {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        // The first two lines are the `---` and `+++` file headers
+        let style = if i < 2 {
+            BOLD
+        } else if text.starts_with("@@") {
+            CYAN
+        } else if text.starts_with('-') {
+            RED
+        } else if text.starts_with('+') {
+            GREEN
+        } else {
+            ""
+        };
+
+        if style.is_empty() {
+            output.push_str(line);
+        } else {
+            output.push_str(&format!("{style}{text}{RESET}{newline}"));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let original = "# Title\n\n| a | b |\n|-|-|\n| 1 | 2 |\n";
+        let formatted = "# Title\n\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+
+        assert_eq!(
+            unified_diff(original, formatted, "doc.md", false),
+            "--- doc.md\n+++ doc.md\n@@ -1,5 +1,5 @@\n # Title\n \n | a | b |\n-|-|-|\n+|---|---|\n | 1 | 2 |\n"
+        );
+        assert_eq!(unified_diff(original, original, "doc.md", false), "");
+    }
+
+    #[test]
+    fn test_crlf_document_diff_shows_only_changed_lines() {
+        let original = "# Title\r\n\r\n| a |\r\n|-|\r\n";
+        let formatted = ftb::TableFormatter::new().format_document(original);
+
+        assert_eq!(
+            unified_diff(original, &formatted, "doc.md", false),
+            "--- doc.md\n+++ doc.md\n@@ -1,4 +1,4 @@\n # Title\r\n \r\n | a |\r\n-|-|\r\n+|---|\r\n"
+        );
+    }
+
+    #[test]
+    fn test_colored_diff() {
+        let diff = unified_diff("| a |\n|-|\n", "| a |\n|---|\n", "t.md", true);
+
+        assert!(diff.starts_with("\x1b[1m--- t.md\x1b[0m\n"));
+        assert!(diff.contains("\x1b[31m-|-|\x1b[0m\n"));
+        assert!(diff.contains("\x1b[32m+|---|\x1b[0m\n"));
+    }
+}
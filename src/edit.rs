@@ -0,0 +1,482 @@
+//! Structural edits to the Markdown table under a cursor, in the manner of
+//! Emacs table mode.
+
+use crate::{
+    cell_ranges, replace_tables, text_start, Alignment, Dialect, Position, Result, TableError,
+    TableFormatter, TableSpan,
+};
+
+/// An operation on the table under the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableEdit {
+    /// Moves to the next cell, wrapping to the next row and adding an empty
+    /// row after the last one.
+    NextCell,
+
+    /// Moves to the previous cell, wrapping to the end of the previous row.
+    PreviousCell,
+
+    /// Inserts an empty row below the cursor row.
+    InsertRowBelow,
+
+    /// Deletes the cursor row. The header row cannot be deleted.
+    DeleteRow,
+
+    /// Inserts an empty column left of the cursor column.
+    InsertColumnLeft,
+
+    /// Inserts an empty column right of the cursor column.
+    InsertColumnRight,
+
+    /// Deletes the cursor column, unless it is the only one.
+    DeleteColumn,
+
+    /// Swaps the cursor column with the one on its left.
+    MoveColumnLeft,
+
+    /// Swaps the cursor column with the one on its right.
+    MoveColumnRight,
+
+    /// Swaps the cursor row with the body row above it.
+    MoveRowUp,
+
+    /// Swaps the cursor row with the body row below it.
+    MoveRowDown,
+}
+
+/// A document after a [`TableEdit`], with the cursor moved to follow it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditedDocument {
+    /// The whole document, with the edited table reformatted.
+    pub text: String,
+
+    /// The new cursor, as a byte offset into `text`.
+    pub cursor: usize,
+}
+
+/// The rows of a table being edited, as the formatter holds them: the header
+/// at index 0, the separator at index 1 and the body rows after it.
+struct Grid {
+    cells: Vec<Vec<String>>,
+    alignments: Vec<Alignment>,
+    row: usize,
+    col: usize,
+}
+
+/// Index of the first body row.
+const FIRST_BODY_ROW: usize = 2;
+
+impl Grid {
+    fn columns(&self) -> usize {
+        self.alignments.len()
+    }
+
+    fn empty_row(&self) -> Vec<String> {
+        vec![String::new(); self.columns()]
+    }
+
+    /// The row after `row`, stepping over the separator.
+    fn next_row(row: usize) -> usize {
+        if row == 0 {
+            FIRST_BODY_ROW
+        } else {
+            row + 1
+        }
+    }
+
+    /// The row before `row`, stepping over the separator.
+    fn previous_row(row: usize) -> usize {
+        if row == FIRST_BODY_ROW {
+            0
+        } else {
+            row.saturating_sub(1)
+        }
+    }
+
+    fn insert_column(&mut self, at: usize) {
+        for (row_i, row) in self.cells.iter_mut().enumerate() {
+            let cell = if row_i == 1 { "-" } else { "" };
+            row.insert(at, cell.to_string());
+        }
+        self.alignments.insert(at, Alignment::None);
+        self.col = at;
+    }
+
+    fn swap_columns(&mut self, a: usize, b: usize) {
+        for row in &mut self.cells {
+            row.swap(a, b);
+        }
+        self.alignments.swap(a, b);
+    }
+
+    /// Applies an edit, returning whether the cursor keeps its offset in the
+    /// cell rather than moving to the start of the cell's text.
+    fn apply(&mut self, edit: TableEdit) -> Result<bool> {
+        let columns = self.columns();
+
+        match edit {
+            TableEdit::NextCell => {
+                if self.col + 1 < columns {
+                    self.col += 1;
+                } else {
+                    self.col = 0;
+                    self.row = Self::next_row(self.row);
+                    if self.row == self.cells.len() {
+                        self.cells.push(self.empty_row());
+                    }
+                }
+            }
+            TableEdit::PreviousCell => {
+                if self.col > 0 {
+                    self.col -= 1;
+                } else if self.row > 0 {
+                    self.row = Self::previous_row(self.row);
+                    self.col = columns - 1;
+                }
+            }
+            TableEdit::InsertRowBelow => {
+                self.row = Self::next_row(self.row);
+                self.cells.insert(self.row, self.empty_row());
+            }
+            TableEdit::DeleteRow => {
+                if self.row == 0 {
+                    return Err(TableError::Unsupported(
+                        "the header row cannot be deleted".to_string(),
+                    ));
+                }
+                self.cells.remove(self.row);
+                if self.row == self.cells.len() {
+                    self.row = Self::previous_row(self.row);
+                }
+            }
+            TableEdit::InsertColumnLeft => self.insert_column(self.col),
+            TableEdit::InsertColumnRight => self.insert_column(self.col + 1),
+            TableEdit::DeleteColumn => {
+                if columns == 1 {
+                    return Err(TableError::Unsupported(
+                        "a table needs at least one column".to_string(),
+                    ));
+                }
+                for row in &mut self.cells {
+                    row.remove(self.col);
+                }
+                self.alignments.remove(self.col);
+                self.col = self.col.min(columns - 2);
+            }
+            TableEdit::MoveColumnLeft => {
+                if self.col > 0 {
+                    self.swap_columns(self.col, self.col - 1);
+                    self.col -= 1;
+                }
+                return Ok(true);
+            }
+            TableEdit::MoveColumnRight => {
+                if self.col + 1 < columns {
+                    self.swap_columns(self.col, self.col + 1);
+                    self.col += 1;
+                }
+                return Ok(true);
+            }
+            TableEdit::MoveRowUp => {
+                if self.row > FIRST_BODY_ROW {
+                    self.cells.swap(self.row, self.row - 1);
+                    self.row -= 1;
+                }
+                return Ok(true);
+            }
+            TableEdit::MoveRowDown => {
+                if self.row >= FIRST_BODY_ROW && self.row + 1 < self.cells.len() {
+                    self.cells.swap(self.row, self.row + 1);
+                    self.row += 1;
+                }
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl TableFormatter {
+    /// Applies `edit` to the Markdown table containing the byte offset
+    /// `cursor`, reformats that table and moves the cursor to follow the edit.
+    ///
+    /// Moving to a cell or inserting one puts the cursor at the start of the
+    /// cell's text. Moving a row or column keeps the cursor at the same place
+    /// within its cell. Other tables in the document are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TableError::EmptyInput`] when the cursor is not inside a
+    /// table that can be formatted, and [`TableError::Unsupported`] when
+    /// deleting the header row or the only column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{TableEdit, TableFormatter};
+    ///
+    /// let doc = "| a | b |\n|-|-|\n| 1 | 2 |\n";
+    /// // The cursor is on the `2`
+    /// let edited = TableFormatter::new().edit_table(doc, 22, TableEdit::NextCell).unwrap();
+    /// assert_eq!(edited.text, "| a | b |\n|---|---|\n| 1 | 2 |\n|   |   |\n");
+    /// assert_eq!(edited.cursor, 32);
+    /// ```
+    pub fn edit_table(
+        &mut self,
+        document: &str,
+        cursor: usize,
+        edit: TableEdit,
+    ) -> Result<EditedDocument> {
+        let position = position_at(document, cursor);
+        let span = self
+            .table_spans(document, Dialect::Markdown)
+            .into_iter()
+            .find(|span| span.start_line <= position.line && position.line < span.end_line)
+            .ok_or(TableError::EmptyInput)?;
+
+        let lines: Vec<&str> = document.lines().collect();
+        self.load_table(&lines[span.start_line..span.end_line].join("\n"))?;
+        self.get_column_widths();
+        self.add_missing_cell_columns();
+
+        let line = lines[position.line];
+        let chars: Vec<char> = line.chars().collect();
+        let ranges = cell_ranges(&chars);
+        let range_index = ranges
+            .iter()
+            .rposition(|&(start, _)| start <= position.column)
+            .unwrap_or(0);
+        // The text before a leading pipe is not a cell
+        let leading_pipe = line.trim_start().starts_with('|');
+        let index = if leading_pipe {
+            range_index.saturating_sub(1)
+        } else {
+            range_index
+        };
+        let (start, end) = ranges[(index + usize::from(leading_pipe)).min(ranges.len() - 1)];
+        let offset = position
+            .column
+            .saturating_sub(text_start(&chars, start, end));
+
+        let columns = self.column_widths.len();
+        let mut alignments = std::mem::take(&mut self.alignments);
+        alignments.resize(columns, Alignment::None);
+        let row = position.line - span.start_line;
+        let mut grid = Grid {
+            cells: std::mem::take(&mut self.cells),
+            alignments,
+            // The separator row belongs with the header
+            row: if row == 1 { 0 } else { row },
+            col: index.min(columns.saturating_sub(1)),
+        };
+        let keep_offset = grid.apply(edit)?;
+
+        self.cells = grid.cells;
+        self.alignments = grid.alignments;
+        self.get_column_widths();
+        self.pad_cells_for_output();
+        let formatted = self.render_output();
+
+        let column = cell_column(
+            formatted.lines().nth(grid.row).unwrap_or_default(),
+            grid.col,
+            if keep_offset { offset } else { 0 },
+        );
+        let text = replace_tables(
+            document,
+            &[TableSpan {
+                start_line: span.start_line,
+                end_line: span.end_line,
+                formatted,
+            }],
+        );
+        let cursor = offset_at(
+            &text,
+            Position {
+                line: span.start_line + grid.row,
+                column,
+            },
+        );
+
+        Ok(EditedDocument { text, cursor })
+    }
+}
+
+/// Returns the character column `offset` characters into the text of cell
+/// `col` of a formatted table line, staying within the cell.
+fn cell_column(line: &str, col: usize, offset: usize) -> usize {
+    let chars: Vec<char> = line.chars().collect();
+    let ranges = cell_ranges(&chars);
+    // Formatted lines start with a pipe, so cell `col` is range `col + 1`
+    let (start, end) = ranges[(col + 1).min(ranges.len() - 1)];
+    let text = text_start(&chars, start, end);
+    // An empty cell has no text, so go just past its leading space
+    let text = if text == end {
+        (start + 1).min(end)
+    } else {
+        text
+    };
+
+    (text + offset).min(end)
+}
+
+/// Converts a byte offset into a line and character column, clamping it to
+/// the document and to a character boundary.
+fn position_at(document: &str, offset: usize) -> Position {
+    let mut offset = offset.min(document.len());
+    while !document.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &document[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count(),
+        column: before[line_start..].chars().count(),
+    }
+}
+
+/// Converts a line and character column into a byte offset, clamping the
+/// column to the end of its line.
+fn offset_at(document: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for line in document.split_inclusive('\n').take(position.line) {
+        line_start += line.len();
+    }
+
+    let line = document[line_start..].lines().next().unwrap_or_default();
+    let column = line
+        .char_indices()
+        .nth(position.column)
+        .map_or(line.len(), |(i, _)| i);
+    line_start + column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "Intro\n\n| a | b |\n|-|--:|\n| 1 | 2 |\n| 3 | 4 |\n";
+
+    /// Applies an edit with the cursor placed just before `marker`.
+    fn edit(document: &str, marker: &str, edit: TableEdit) -> Result<EditedDocument> {
+        let cursor = document
+            .find(marker)
+            .expect("Marker should be in the document");
+        TableFormatter::new().edit_table(document, cursor, edit)
+    }
+
+    /// Returns the rest of the line after the cursor.
+    fn at_cursor(edited: &EditedDocument) -> &str {
+        edited.text[edited.cursor..]
+            .lines()
+            .next()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_next_and_previous_cell() {
+        let edited = edit(DOC, "1 |", TableEdit::NextCell).unwrap();
+        assert_eq!(at_cursor(&edited), "2 |");
+
+        let edited = edit(DOC, "a |", TableEdit::PreviousCell).unwrap();
+        assert_eq!(at_cursor(&edited), "a | b |");
+
+        // From the end of the header to the first body row
+        let edited = edit(DOC, "b |", TableEdit::NextCell).unwrap();
+        assert_eq!(at_cursor(&edited), "1 | 2 |");
+
+        let edited = edit(DOC, "1 |", TableEdit::PreviousCell).unwrap();
+        assert_eq!(at_cursor(&edited), "b |");
+    }
+
+    #[test]
+    fn test_insert_and_delete_rows() {
+        let edited = edit(DOC, "1 |", TableEdit::InsertRowBelow).unwrap();
+        assert_eq!(
+            edited.text,
+            "Intro\n\n| a | b |\n|---|--:|\n| 1 | 2 |\n|   |   |\n| 3 | 4 |\n"
+        );
+        assert_eq!(at_cursor(&edited), "  |   |");
+
+        let edited = edit(DOC, "3 |", TableEdit::DeleteRow).unwrap();
+        assert_eq!(edited.text, "Intro\n\n| a | b |\n|---|--:|\n| 1 | 2 |\n");
+        assert_eq!(at_cursor(&edited), "1 | 2 |");
+
+        assert!(matches!(
+            edit(DOC, "a |", TableEdit::DeleteRow),
+            Err(TableError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_column_edits() {
+        let edited = edit(DOC, "b |", TableEdit::InsertColumnLeft).unwrap();
+        assert_eq!(
+            edited.text,
+            "Intro\n\n| a |   | b |\n|---|---|--:|\n| 1 |   | 2 |\n| 3 |   | 4 |\n"
+        );
+        assert_eq!(at_cursor(&edited), "  | b |");
+
+        let edited = edit(DOC, "a |", TableEdit::DeleteColumn).unwrap();
+        assert_eq!(edited.text, "Intro\n\n| b |\n|--:|\n| 2 |\n| 4 |\n");
+        assert_eq!(at_cursor(&edited), "b |");
+
+        // The alignment moves with the column and the cursor follows it
+        let edited = edit(DOC, "1 |", TableEdit::MoveColumnRight).unwrap();
+        assert_eq!(
+            edited.text,
+            "Intro\n\n| b | a |\n|--:|---|\n| 2 | 1 |\n| 4 | 3 |\n"
+        );
+        assert_eq!(at_cursor(&edited), "1 |");
+    }
+
+    #[test]
+    fn test_table_without_outer_pipes() {
+        let doc = "abc | d\n--- | ---\n";
+        let edited = edit(doc, "c |", TableEdit::MoveColumnRight).unwrap();
+        assert_eq!(edited.text, "| d | abc |\n|---|-----|\n");
+        assert_eq!(at_cursor(&edited), "c |");
+    }
+
+    #[test]
+    fn test_move_rows() {
+        let edited = edit(DOC, "3 |", TableEdit::MoveRowUp).unwrap();
+        assert_eq!(
+            edited.text,
+            "Intro\n\n| a | b |\n|---|--:|\n| 3 | 4 |\n| 1 | 2 |\n"
+        );
+        assert_eq!(at_cursor(&edited), "3 | 4 |");
+
+        // The header does not move
+        let edited = edit(DOC, "a |", TableEdit::MoveRowDown).unwrap();
+        assert!(edited.text.contains("| a | b |\n|---|--:|\n| 1 |"));
+    }
+
+    #[test]
+    fn test_edit_outside_table() {
+        assert_eq!(
+            edit(DOC, "Intro", TableEdit::NextCell),
+            Err(TableError::EmptyInput)
+        );
+    }
+
+    #[test]
+    fn test_offsets_count_bytes() {
+        let doc = "| é | b |\n|-|-|\n";
+        assert_eq!(position_at(doc, 4), Position { line: 0, column: 3 });
+        // Inside a multi-byte character
+        assert_eq!(position_at(doc, 3), Position { line: 0, column: 2 });
+        assert_eq!(
+            offset_at(
+                doc,
+                Position {
+                    line: 1,
+                    column: 99
+                }
+            ),
+            16
+        );
+    }
+}
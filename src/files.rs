@@ -0,0 +1,213 @@
+//! Finding the documents to format and rewriting them in place.
+
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use ignore::WalkBuilder;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process;
+
+/// Expands command-line paths into the files to format, in a stable order.
+///
+/// Files named directly are always included. Directories are walked
+/// recursively for the files accepted by `select`, and glob patterns such as
+/// `docs/**/*.md` are matched below their literal prefix. Both skip hidden
+/// files and anything excluded by `.gitignore`.
+pub fn collect_files(
+    paths: &[PathBuf],
+    mut select: impl FnMut(&Path) -> Result<bool, String>,
+) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            for file in walk(path) {
+                if select(&file)? {
+                    files.push(file);
+                }
+            }
+        } else if !path.exists() && is_glob(path) {
+            let matcher = glob_matcher(path)?;
+            let matches: Vec<PathBuf> = walk(&glob_base(path))
+                .filter(|file| matcher.is_match(strip_dot(file)))
+                .collect();
+            if matches.is_empty() {
+                return Err(format!("No files match {}", path.display()));
+            }
+            files.extend(matches);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    // A file reached through two arguments is formatted once
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+
+    Ok(files)
+}
+
+/// Checks whether a file has one of the given extensions, ignoring case.
+pub fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extensions.iter().any(|wanted| {
+                wanted
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(extension)
+            })
+        })
+}
+
+/// Replaces the contents of a file atomically, keeping its permissions.
+///
+/// The new contents are written to a temporary file in the same directory,
+/// which is then renamed over the original, so readers never see a partly
+/// written file. Symlinks are followed, leaving the link itself in place.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let permissions = fs::metadata(&path)?.permissions();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{name}.ftb-{}.tmp", process::id()));
+
+    let result = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::set_permissions(&temp, permissions)?;
+        fs::rename(&temp, &path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+
+    result
+}
+
+/// Walks a directory, honoring `.gitignore`, and yields the files in it.
+fn walk(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkBuilder::new(dir)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .map(ignore::DirEntry::into_path)
+}
+
+/// Checks whether a path contains glob metacharacters.
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '[', '{'])
+}
+
+/// Parses a glob pattern in which `*` stops at `/` and `**` crosses directories.
+pub(crate) fn glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern).literal_separator(true).build()
+}
+
+/// Compiles a glob pattern given on the command line.
+fn glob_matcher(pattern: &Path) -> Result<GlobMatcher, String> {
+    let pattern = strip_dot(pattern).to_string_lossy();
+    glob(&pattern)
+        .map(|glob| glob.compile_matcher())
+        .map_err(|e| format!("Invalid glob pattern {pattern}: {e}"))
+}
+
+/// Returns the leading directories of a glob pattern that contain no metacharacters.
+fn glob_base(pattern: &Path) -> PathBuf {
+    let base: PathBuf = pattern
+        .components()
+        .take_while(|component| !is_glob(Path::new(component.as_os_str())))
+        .collect();
+
+    if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    }
+}
+
+/// Removes a leading `./`, which walking the current directory adds to every path.
+fn strip_dot(path: &Path) -> &Path {
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::CurDir) => components.as_path(),
+        _ => path,
+    }
+}
+
+/// Creates an empty directory under the system temp directory for a test.
+#[cfg(test)]
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ftb-{name}-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Should create temp dir");
+    fs::canonicalize(&dir).expect("Should resolve temp dir")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_files_walks_directories() {
+        let dir = temp_dir("walk");
+        fs::create_dir_all(dir.join("docs/build")).unwrap();
+        fs::write(dir.join(".gitignore"), "build/\n").unwrap();
+        fs::write(dir.join("README.md"), "").unwrap();
+        fs::write(dir.join("docs/guide.markdown"), "").unwrap();
+        fs::write(dir.join("docs/notes.txt"), "").unwrap();
+        fs::write(dir.join("docs/build/out.md"), "").unwrap();
+
+        let extensions = vec!["md".to_string(), "markdown".to_string()];
+        let files = collect_files(std::slice::from_ref(&dir), |file| {
+            Ok(has_extension(file, &extensions))
+        })
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![dir.join("README.md"), dir.join("docs/guide.markdown")]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_collect_files_expands_globs() {
+        let dir = temp_dir("glob");
+        fs::create_dir_all(dir.join("docs/api")).unwrap();
+        fs::write(dir.join("docs/intro.md"), "").unwrap();
+        fs::write(dir.join("docs/api/table.md"), "").unwrap();
+
+        let nested = collect_files(&[dir.join("docs/**/*.md")], |_| Ok(true)).unwrap();
+        assert_eq!(
+            nested,
+            vec![dir.join("docs/api/table.md"), dir.join("docs/intro.md")]
+        );
+
+        let top = collect_files(&[dir.join("docs/*.md")], |_| Ok(true)).unwrap();
+        assert_eq!(top, vec![dir.join("docs/intro.md")]);
+
+        assert!(collect_files(&[dir.join("*.rst")], |_| Ok(true)).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("write");
+        let path = dir.join("table.md");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+//! Pandoc grid tables, whose cells may span several lines and hold lists or
+//! multiple paragraphs.
+//!
+//! ```text
+//! +---------+:-------:+
+//! | Fruit   | Price   |
+//! +=========+=========+
+//! | Bananas | - cheap |
+//! |         | - ripe  |
+//! +---------+---------+
+//! ```
+
+use crate::rst::split_at_display_columns;
+use crate::table::{cell_lines, pad_cell};
+use crate::{Alignment, Result, Table, TableError};
+use unicode_width::UnicodeWidthStr;
+
+/// Parses a single grid table into a [`Table`].
+///
+/// The lines of a multi-line cell are joined with `\n`, keeping their
+/// indentation, so rendering the table as a pipe table joins them with
+/// `<br>`. Alignment is read from colons in the header separator
+/// (`+:===+`), or in the top border of a table without a header. Tables
+/// without a header get an empty header row.
+///
+/// # Errors
+///
+/// Returns `TableError::InvalidStructure` if the input is not a grid table,
+/// and `TableError::Unsupported` if it has merged cells or more than one
+/// header row.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::Alignment;
+///
+/// let input = "+-----+------+\n| a   | b    |\n+=====+=====:+\n| one | 1    |\n| two |      |\n+-----+------+";
+/// let table = ftb::grid::parse_table(input).unwrap();
+/// assert_eq!(table.header, vec!["a", "b"]);
+/// assert_eq!(table.rows, vec![vec!["one\ntwo", "1"]]);
+/// assert_eq!(table.alignment(1), Alignment::Right);
+/// ```
+pub fn parse_table(table: &str) -> Result<Table> {
+    let lines: Vec<&str> = table
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let first = lines
+        .first()
+        .filter(|line| is_border(line))
+        .ok_or_else(|| {
+            TableError::InvalidStructure("Grid table must start with a border".to_string())
+        })?;
+    let boundaries: Vec<usize> = first
+        .char_indices()
+        .filter(|(_, c)| *c == '+')
+        .map(|(i, _)| i)
+        .collect();
+    let num_columns = boundaries.len() - 1;
+
+    let mut alignments = border_alignments(first);
+    let mut header: Option<Vec<String>> = None;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell_texts: Vec<Vec<&str>> = vec![Vec::new(); num_columns];
+    let mut has_content = false;
+
+    for (line_i, line) in lines.iter().enumerate().skip(1) {
+        if is_border(line) {
+            if border_alignments(line).len() != num_columns {
+                return Err(TableError::Unsupported(format!(
+                    "merged cells in grid table border on line {}",
+                    line_i + 1
+                )));
+            }
+
+            let row: Vec<String> = cell_texts
+                .iter_mut()
+                .map(|texts| join_lines(texts))
+                .collect();
+            if line.contains('=') {
+                if header.is_some() || !rows.is_empty() || !has_content {
+                    return Err(TableError::Unsupported(format!(
+                        "header separator on line {} must follow a single header row",
+                        line_i + 1
+                    )));
+                }
+                alignments = border_alignments(line);
+                header = Some(row);
+            } else if has_content {
+                rows.push(row);
+            }
+            has_content = false;
+            continue;
+        }
+
+        let cells = split_at_display_columns(line, &boundaries).ok_or_else(|| {
+            TableError::Unsupported(format!(
+                "merged or misaligned cells in grid table on line {}",
+                line_i + 1
+            ))
+        })?;
+        for (col_i, cell) in cells.into_iter().enumerate() {
+            cell_texts[col_i].push(cell);
+        }
+        has_content = true;
+    }
+
+    if has_content {
+        return Err(TableError::InvalidStructure(
+            "Grid table must end with a border".to_string(),
+        ));
+    }
+
+    let header = header.unwrap_or_else(|| vec![String::new(); num_columns]);
+    let mut table = Table::new(header, rows);
+    table.alignments = alignments;
+    Ok(table)
+}
+
+/// Parses every grid table in a document, in document order.
+///
+/// Blocks that look like grid tables but cannot be parsed are skipped.
+#[must_use]
+pub fn parse_tables(document: &str) -> Vec<Table> {
+    let lines: Vec<&str> = document.lines().collect();
+    let mut tables = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        if !is_border(lines[i].trim()) {
+            i += 1;
+            continue;
+        }
+
+        let mut end = i + 1;
+        let mut last_border = i;
+        while end < lines.len() {
+            let line = lines[end].trim();
+            if is_border(line) {
+                last_border = end;
+            } else if !line.starts_with('|') {
+                break;
+            }
+            end += 1;
+        }
+
+        if let Ok(table) = parse_table(&lines[i..=last_border].join("\n")) {
+            tables.push(table);
+        }
+        i = last_border + 1;
+    }
+
+    tables
+}
+
+/// Renders a table as a Pandoc grid table.
+///
+/// Line breaks and `<br>` tags in cells become separate lines within the
+/// cell, and column alignment becomes colons in the header separator. A
+/// table whose header is entirely empty is written without a header row.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| a | b |\n|-|:-:|\n| one<br>two | x |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::grid::write_table(&table),
+///     "+-----+---+\n| a   | b |\n+=====+:=:+\n| one | x |\n| two |   |\n+-----+---+\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table) -> String {
+    let num_columns = table.column_count();
+    let has_header = table.header.iter().any(|cell| !cell.trim().is_empty());
+    let rows: Vec<Vec<Vec<&str>>> = std::iter::once(&table.header)
+        .filter(|_| has_header)
+        .chain(&table.rows)
+        .map(|row| {
+            (0..num_columns)
+                .map(|col_i| cell_lines(row.get(col_i).map_or("", String::as_str)))
+                .collect()
+        })
+        .collect();
+
+    let mut widths = vec![1; num_columns];
+    for row in &rows {
+        for (col_i, lines) in row.iter().enumerate() {
+            for line in lines {
+                widths[col_i] = widths[col_i].max(line.width());
+            }
+        }
+    }
+
+    let mut output = if has_header {
+        border(&widths, '-', None)
+    } else {
+        border(&widths, '-', Some(table))
+    };
+    for (row_i, row) in rows.iter().enumerate() {
+        let height = row.iter().map(Vec::len).max().unwrap_or(1);
+        for line_i in 0..height {
+            output.push('|');
+            for (col_i, lines) in row.iter().enumerate() {
+                let text = lines.get(line_i).copied().unwrap_or("");
+                output.push(' ');
+                output.push_str(&pad_cell(text, widths[col_i], Alignment::None));
+                output.push_str(" |");
+            }
+            output.push('\n');
+        }
+
+        if row_i == 0 && has_header {
+            output.push_str(&border(&widths, '=', Some(table)));
+        } else {
+            output.push_str(&border(&widths, '-', None));
+        }
+    }
+
+    output
+}
+
+/// Checks for a grid border such as `+---+:==:+`.
+fn is_border(line: &str) -> bool {
+    line.len() >= 3
+        && line.starts_with('+')
+        && line.ends_with('+')
+        && line.chars().all(|c| matches!(c, '+' | '-' | '=' | ':'))
+        && line.contains(['-', '='])
+}
+
+/// Reads the alignment of each segment of a border from its colons.
+fn border_alignments(border: &str) -> Vec<Alignment> {
+    border
+        .trim_matches('+')
+        .split('+')
+        .map(
+            |segment| match (segment.starts_with(':'), segment.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            },
+        )
+        .collect()
+}
+
+/// Builds a border line, marking alignment with colons when `table` is given.
+fn border(widths: &[usize], fill: char, table: Option<&Table>) -> String {
+    let mut line = String::from("+");
+    for (col_i, &width) in widths.iter().enumerate() {
+        let alignment = table.map_or(Alignment::None, |table| table.alignment(col_i));
+        line.push(if alignment.is_left() { ':' } else { fill });
+        line.extend(std::iter::repeat_n(fill, width));
+        line.push(if alignment.is_right() { ':' } else { fill });
+        line.push('+');
+    }
+    line.push('\n');
+    line
+}
+
+/// Joins the lines of one cell, dropping the padding space after `|`,
+/// trailing whitespace and blank lines at either end.
+fn join_lines(texts: &mut Vec<&str>) -> String {
+    let lines: Vec<&str> = texts
+        .drain(..)
+        .map(|text| text.strip_prefix(' ').unwrap_or(text).trim_end())
+        .collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multi_paragraph_cells() {
+        let input = "\
++---------+--------------------+
+| Fruit   | Advantages         |
++=========+====================+
+| Bananas | - built-in wrapper |
+|         |   - really         |
+|         |                    |
+|         | Second paragraph   |
++---------+--------------------+
+| Oranges | cures scurvy       |
++---------+--------------------+";
+        let table = parse_table(input).expect("Should parse");
+
+        assert_eq!(table.header, vec!["Fruit", "Advantages"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec![
+                    "Bananas",
+                    "- built-in wrapper\n  - really\n\nSecond paragraph"
+                ],
+                vec!["Oranges", "cures scurvy"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_headerless_alignment_from_top_border() {
+        let input = "+:--+--:+\n| a | b |\n+---+---+";
+        let table = parse_table(input).expect("Should parse");
+
+        assert_eq!(table.header, vec!["", ""]);
+        assert_eq!(table.rows, vec![vec!["a", "b"]]);
+        assert_eq!(table.alignments, vec![Alignment::Left, Alignment::Right]);
+    }
+
+    #[test]
+    fn test_parse_rejects_merged_cells() {
+        let input = "+---+---+\n| a | b |\n+===+===+\n| spans |\n+-------+";
+
+        assert!(matches!(
+            parse_table(input),
+            Err(TableError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_pipe_table_round_trip_joins_lines_with_br() {
+        let input = "+---+---+\n| a | b |\n+===+===+\n| 1 | x |\n|   | y |\n+---+---+";
+        let table = parse_table(input).expect("Should parse");
+
+        assert_eq!(
+            table.to_markdown().expect("Should render"),
+            "| a | b      |\n|---|--------|\n| 1 | x<br>y |\n"
+        );
+        assert_eq!(write_table(&table), format!("{input}\n"));
+    }
+
+    #[test]
+    fn test_write_headerless_table() {
+        let mut table = Table::new(
+            vec![String::new()],
+            vec![vec!["x".to_string()], vec!["yy".to_string()]],
+        );
+        table.alignments = vec![Alignment::Right];
+
+        assert_eq!(
+            write_table(&table),
+            "+---:+\n| x  |\n+----+\n| yy |\n+----+\n"
+        );
+    }
+}
@@ -0,0 +1,694 @@
+//! Conversion between tables and HTML `<table>` markup.
+
+use crate::table::line_break_len;
+use crate::{Alignment, Result, Table, TableError};
+
+/// Options controlling HTML output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// Emit `style="text-align: …"` instead of the `align` attribute.
+    pub inline_styles: bool,
+}
+
+/// Renders a table as an HTML `<table>` with `<thead>` and `<tbody>`.
+///
+/// Cell content is HTML-escaped. The `<br>` tags used to encode line breaks
+/// in Markdown cells are kept as line breaks. Column alignment from the
+/// separator row is carried over to every cell of the column.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::html::{write_table, HtmlOptions};
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| a | b |\n|-|-:|\n| <x> | 1 |")
+///     .unwrap();
+/// let html = write_table(&table, &HtmlOptions::default());
+/// assert!(html.contains("<th align=\"right\">b</th>"));
+/// assert!(html.contains("<td>&lt;x&gt;</td>"));
+/// ```
+#[must_use]
+pub fn write_table(table: &Table, options: &HtmlOptions) -> String {
+    let num_columns = table.column_count();
+    let mut output = String::from("<table>\n  <thead>\n");
+    push_row(
+        &mut output,
+        table,
+        &table.header,
+        "th",
+        num_columns,
+        options,
+    );
+    output.push_str("  </thead>\n");
+
+    if !table.rows.is_empty() {
+        output.push_str("  <tbody>\n");
+        for row in &table.rows {
+            push_row(&mut output, table, row, "td", num_columns, options);
+        }
+        output.push_str("  </tbody>\n");
+    }
+
+    output.push_str("</table>\n");
+    output
+}
+
+/// Appends one `<tr>`, padding short rows with empty cells.
+fn push_row(
+    output: &mut String,
+    table: &Table,
+    row: &[String],
+    tag: &str,
+    num_columns: usize,
+    options: &HtmlOptions,
+) {
+    output.push_str("    <tr>\n");
+    for col_i in 0..num_columns {
+        let cell = row.get(col_i).map_or("", String::as_str);
+        output.push_str("      <");
+        output.push_str(tag);
+        output.push_str(&alignment_attribute(table.alignment(col_i), options));
+        output.push('>');
+        output.push_str(&escape_cell(cell));
+        output.push_str("</");
+        output.push_str(tag);
+        output.push_str(">\n");
+    }
+    output.push_str("    </tr>\n");
+}
+
+/// Builds the alignment attribute for a cell, including its leading space.
+fn alignment_attribute(alignment: Alignment, options: &HtmlOptions) -> String {
+    let value = match alignment {
+        Alignment::None => return String::new(),
+        Alignment::Left => "left",
+        Alignment::Center => "center",
+        Alignment::Right => "right",
+    };
+
+    if options.inline_styles {
+        format!(" style=\"text-align: {value}\"")
+    } else {
+        format!(" align=\"{value}\"")
+    }
+}
+
+/// Escapes cell text, keeping `<br>` line breaks.
+fn escape_cell(cell: &str) -> String {
+    let mut output = String::with_capacity(cell.len());
+    let mut rest = cell.trim();
+
+    while !rest.is_empty() {
+        if let Some(len) = line_break_len(rest) {
+            output.push_str("<br>");
+            rest = &rest[len..];
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap_or_default();
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            '\n' => output.push_str("<br>"),
+            _ => output.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    output
+}
+
+/// Parses every `<table>` element in an HTML fragment or page.
+///
+/// Supported markup is `<thead>`, `<tbody>`, `<tfoot>`, `<tr>`, `<th>` and
+/// `<td>`, with column alignment read from `align` attributes or
+/// `text-align` styles on the header cells.
+/// Inside cells, `<br>` becomes a line break, `<code>` becomes a code span
+/// and `<a href>` becomes a link; other tags are dropped and their text kept
+/// as plain text. Rows in `<thead>` (or the first row when there is none)
+/// form the header.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there is no table, and
+/// `TableError::Unsupported` for constructs that a Markdown table cannot
+/// express, such as `rowspan`, `colspan` or nested tables.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::html::parse_tables;
+///
+/// let html = "<table><tr><th>Name</th></tr><tr><td><a href=\"/x\">x</a> &amp; <code>y</code></td></tr></table>";
+/// let tables = parse_tables(html).unwrap();
+/// assert_eq!(tables[0].header, vec!["Name"]);
+/// assert_eq!(tables[0].rows, vec![vec!["[x](/x) & `y`"]]);
+/// ```
+pub fn parse_tables(input: &str) -> Result<Vec<Table>> {
+    let mut parser = HtmlTableParser::new(input);
+    parser.parse()?;
+
+    if parser.tables.is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+
+    Ok(parser.tables)
+}
+
+/// Converts the first HTML table in the input into an aligned Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if no table can be parsed or formatted.
+pub fn to_markdown(input: &str) -> Result<String> {
+    let tables = parse_tables(input)?;
+    tables[0].to_markdown()
+}
+
+/// Table section that rows are being read into.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Head,
+    Body,
+}
+
+/// A table being assembled from tags.
+struct TableBuilder {
+    head_rows: Vec<Vec<String>>,
+    body_rows: Vec<Vec<String>>,
+    alignments: Vec<Alignment>,
+    section: Section,
+    row: Option<Vec<String>>,
+    cell: Option<String>,
+    links: Vec<String>,
+}
+
+impl TableBuilder {
+    fn new() -> Self {
+        Self {
+            head_rows: Vec::new(),
+            body_rows: Vec::new(),
+            alignments: Vec::new(),
+            section: Section::Body,
+            row: None,
+            cell: None,
+            links: Vec::new(),
+        }
+    }
+
+    fn start_row(&mut self) {
+        self.end_row();
+        self.row = Some(Vec::new());
+    }
+
+    fn end_row(&mut self) {
+        self.end_cell();
+        if let Some(row) = self.row.take() {
+            match self.section {
+                Section::Head => self.head_rows.push(row),
+                Section::Body => self.body_rows.push(row),
+            }
+        }
+    }
+
+    fn start_cell(&mut self, alignment: Alignment) {
+        self.end_cell();
+        let row = self.row.get_or_insert_with(Vec::new);
+
+        // Alignment is taken from the row that becomes the header
+        let is_header_row = self.head_rows.is_empty()
+            && (self.section == Section::Head || self.body_rows.is_empty());
+        if is_header_row {
+            let col_i = row.len();
+            if self.alignments.len() <= col_i {
+                self.alignments.resize(col_i + 1, Alignment::None);
+            }
+            self.alignments[col_i] = alignment;
+        }
+
+        self.cell = Some(String::new());
+    }
+
+    fn end_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            let text = cell
+                .lines()
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string();
+            self.row.get_or_insert_with(Vec::new).push(text);
+            self.links.clear();
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let Some(cell) = self.cell.as_mut() else {
+            return;
+        };
+
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !cell.is_empty() && !cell.ends_with([' ', '\n']) {
+                    cell.push(' ');
+                }
+            } else {
+                cell.push(c);
+            }
+        }
+    }
+
+    fn push_markup(&mut self, markup: &str) {
+        if let Some(cell) = self.cell.as_mut() {
+            cell.push_str(markup);
+        }
+    }
+
+    fn finish(mut self) -> Table {
+        self.end_row();
+
+        let (header, rows) = if self.head_rows.is_empty() {
+            let mut rows = self.body_rows.into_iter();
+            (rows.next().unwrap_or_default(), rows.collect())
+        } else {
+            let mut head = self.head_rows.into_iter();
+            let header = head.next().unwrap_or_default();
+            (header, head.chain(self.body_rows).collect())
+        };
+
+        let mut table = Table::new(header, rows);
+        table.alignments = self.alignments;
+        table
+    }
+}
+
+/// A minimal HTML tokenizer that only understands table markup.
+struct HtmlTableParser<'a> {
+    input: &'a str,
+    pos: usize,
+    tables: Vec<Table>,
+    current: Option<TableBuilder>,
+}
+
+impl<'a> HtmlTableParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            tables: Vec::new(),
+            current: None,
+        }
+    }
+
+    fn parse(&mut self) -> Result<()> {
+        while self.pos < self.input.len() {
+            let rest = &self.input[self.pos..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                self.pos += comment.find("-->").map_or(rest.len(), |end| end + 7);
+                continue;
+            }
+
+            if rest.starts_with('<') {
+                if let Some(tag) = Tag::parse(rest) {
+                    let tag_start = self.pos;
+                    self.pos += tag.len;
+                    self.handle_tag(&tag, tag_start)?;
+                    continue;
+                }
+            }
+
+            let first_len = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first_len..]
+                .find('<')
+                .map_or(rest.len(), |i| i + first_len);
+            let text = decode_entities(&rest[..end]);
+            if let Some(table) = self.current.as_mut() {
+                table.push_text(&text);
+            }
+            self.pos += end;
+        }
+
+        if let Some(table) = self.current.take() {
+            self.tables.push(table.finish());
+        }
+
+        Ok(())
+    }
+
+    fn handle_tag(&mut self, tag: &Tag, offset: usize) -> Result<()> {
+        if tag.name == "table" {
+            if tag.closing {
+                if let Some(table) = self.current.take() {
+                    self.tables.push(table.finish());
+                }
+            } else if self.current.is_some() {
+                return Err(self.unsupported("nested <table>", offset));
+            } else {
+                self.current = Some(TableBuilder::new());
+            }
+            return Ok(());
+        }
+
+        let Some(table) = self.current.as_mut() else {
+            return Ok(());
+        };
+
+        match (tag.name.as_str(), tag.closing) {
+            ("thead", false) => {
+                table.end_row();
+                table.section = Section::Head;
+            }
+            ("tbody" | "tfoot", false) | ("thead", true) => {
+                table.end_row();
+                table.section = Section::Body;
+            }
+            ("tr", false) => table.start_row(),
+            ("tr", true) => table.end_row(),
+            ("th" | "td", false) => {
+                for span in ["rowspan", "colspan"] {
+                    if tag.attribute(span).is_some_and(|value| value.trim() != "1") {
+                        let message = format!("{span} on <{}>", tag.name);
+                        return Err(self.unsupported(&message, offset));
+                    }
+                }
+                table.start_cell(tag.alignment());
+            }
+            ("th" | "td", true) => table.end_cell(),
+            ("br", _) => table.push_markup("\n"),
+            ("p" | "div" | "li", false)
+                if table
+                    .cell
+                    .as_ref()
+                    .is_some_and(|cell| !cell.trim().is_empty()) =>
+            {
+                table.push_markup("\n");
+            }
+            ("code", _) => table.push_markup("`"),
+            ("a", false) => {
+                table.push_markup("[");
+                table.links.push(tag.attribute("href").unwrap_or_default());
+            }
+            ("a", true) => {
+                let href = table.links.pop().unwrap_or_default();
+                table.push_markup(&format!("]({href})"));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn unsupported(&self, construct: &str, offset: usize) -> TableError {
+        let line = self.input[..offset].matches('\n').count() + 1;
+        TableError::Unsupported(format!("{construct} on line {line}"))
+    }
+}
+
+/// An opening or closing tag with its attributes.
+struct Tag {
+    name: String,
+    closing: bool,
+    attributes: Vec<(String, String)>,
+    len: usize,
+}
+
+impl Tag {
+    /// Parses the tag at the start of `text`, or returns `None` if `<` does
+    /// not start a tag.
+    fn parse(text: &str) -> Option<Self> {
+        let end = tag_end(text)?;
+        let inner = &text[1..end];
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let inner = inner.trim_end_matches('/');
+
+        let name_end = inner
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(inner.len());
+        let name = inner[..name_end].to_ascii_lowercase();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            closing,
+            attributes: parse_attributes(&inner[name_end..]),
+            len: end + 1,
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn alignment(&self) -> Alignment {
+        attributes_alignment(&self.attributes)
+    }
+}
+
+/// Finds the `>` that closes the tag at the start of `text`, skipping any
+/// inside quoted attribute values.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '>') => return Some(i),
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Reads alignment from an `align` attribute or a `text-align` style.
+pub(crate) fn attributes_alignment(attributes: &[(String, String)]) -> Alignment {
+    let attribute = |name: &str| {
+        attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_ascii_lowercase())
+    };
+    let style = attribute("style").unwrap_or_default();
+    let value = attribute("align").or_else(|| {
+        style
+            .split(';')
+            .filter_map(|declaration| declaration.split_once(':'))
+            .find(|(property, _)| property.trim() == "text-align")
+            .map(|(_, value)| value.trim().to_string())
+    });
+
+    match value.as_deref() {
+        Some("left") => Alignment::Left,
+        Some("center") => Alignment::Center,
+        Some("right") => Alignment::Right,
+        _ => Alignment::None,
+    }
+}
+
+/// Parses `key="value"`, `key='value'`, `key=value` and bare `key` attributes.
+pub(crate) fn parse_attributes(text: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (raw, remaining) = match after_eq.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after_eq[1..];
+                    let close = body.find(quote).unwrap_or(body.len());
+                    (&body[..close], body.get(close + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    (&after_eq[..end], &after_eq[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining.trim_start();
+        }
+
+        if !key.is_empty() {
+            attributes.push((key, value));
+        }
+    }
+
+    attributes
+}
+
+/// Decodes the common named entities and numeric character references.
+fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        output.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity.strip_prefix('#').and_then(|number| {
+                    match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => number.parse().ok(),
+                    }
+                    .and_then(char::from_u32)
+                }),
+            };
+            c.map(|c| (c, end + 1))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(header: &[&str], rows: &[&[&str]]) -> Table {
+        let strings = |cells: &[&str]| cells.iter().map(|cell| (*cell).to_string()).collect();
+        Table::new(
+            strings(header),
+            rows.iter().map(|row| strings(row)).collect(),
+        )
+    }
+
+    #[test]
+    fn test_write_table_structure() {
+        let table = table(&["a", "b"], &[&["1"]]);
+
+        assert_eq!(
+            write_table(&table, &HtmlOptions::default()),
+            "<table>\n  <thead>\n    <tr>\n      <th>a</th>\n      <th>b</th>\n    </tr>\n  </thead>\n  <tbody>\n    <tr>\n      <td>1</td>\n      <td></td>\n    </tr>\n  </tbody>\n</table>\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_style_alignment() {
+        let mut table = table(&["a"], &[]);
+        table.alignments = vec![Alignment::Center];
+        let html = write_table(
+            &table,
+            &HtmlOptions {
+                inline_styles: true,
+            },
+        );
+
+        assert!(html.contains("<th style=\"text-align: center\">a</th>"));
+        assert!(!html.contains("<tbody>"));
+    }
+
+    #[test]
+    fn test_parse_thead_and_alignment() {
+        let html = "<table>\n<thead><tr><th align=\"right\">Qty</th><th style=\"text-align: center\">Item</th></tr></thead>\n<tbody><tr><td>1</td><td>tea<br>green</td></tr></tbody>\n</table>";
+        let tables = parse_tables(html).expect("Should parse");
+
+        assert_eq!(tables[0].header, vec!["Qty", "Item"]);
+        assert_eq!(tables[0].rows, vec![vec!["1", "tea\ngreen"]]);
+        assert_eq!(
+            tables[0].alignments,
+            vec![Alignment::Right, Alignment::Center]
+        );
+    }
+
+    #[test]
+    fn test_parse_collapses_whitespace_and_decodes_entities() {
+        let html = "<table><tr><td>  a\n   b&nbsp;&#x41;&#66;</td><td><em>x</em> &lt;y&gt;</td></tr></table>";
+        let tables = parse_tables(html).expect("Should parse");
+
+        assert_eq!(tables[0].header, vec!["a b AB", "x <y>"]);
+    }
+
+    #[test]
+    fn test_parse_quoted_gt_and_markdown_characters() {
+        let html = "<table><tr><th title=\"a>b\">x</th><th data-x='>'>y</th></tr><tr><td>1 &lt; 2 *bold* `c` [d]</td><td>a\\b</td></tr></table>";
+        let tables = parse_tables(html).expect("Should parse");
+
+        assert_eq!(tables[0].header, vec!["x", "y"]);
+        assert_eq!(tables[0].rows, vec![vec!["1 < 2 *bold* `c` [d]", "a\\b"]]);
+    }
+
+    #[test]
+    fn test_parse_keeps_text_plain_for_csv() {
+        let html = "<table><tr><th>a</th></tr><tr><td>1 &lt; 2 *x* a\\b_c</td></tr></table>";
+        let tables = parse_tables(html).expect("Should parse");
+
+        assert_eq!(
+            crate::csv::write_table(&tables[0], ','),
+            "a\n1 < 2 *x* a\\b_c\n"
+        );
+        assert_eq!(
+            to_markdown(html).expect("Should convert"),
+            "| a                   |\n|---------------------|\n| 1 \\< 2 \\*x\\* a\\b\\_c |\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_colspan() {
+        let html = "<table>\n<tr><th>a</th></tr>\n<tr><td colspan=\"2\">b</td></tr>\n</table>";
+        let result = parse_tables(html);
+
+        assert_eq!(
+            result,
+            Err(TableError::Unsupported(
+                "colspan on <td> on line 3".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_without_table_error() {
+        assert_eq!(parse_tables("<p>hi</p>"), Err(TableError::EmptyInput));
+    }
+
+    #[test]
+    fn test_escape_cell_keeps_line_breaks() {
+        assert_eq!(
+            escape_cell("a & \"b\"<BR/>c <script>"),
+            "a &amp; &quot;b&quot;<br>c &lt;script&gt;"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Jira and Confluence wiki markup tables.
+//!
+//! ```text
+//! ||Name||Qty||
+//! |tea|1|
+//! |cake|2|
+//! ```
+
+use crate::table::cell_lines;
+use crate::{Result, Table, TableError};
+
+/// Parses every wiki markup table in the input, in document order.
+///
+/// A table is a run of lines starting with `|`; lines that do not start with
+/// `|` continue the last cell of the row above, and a blank line ends the
+/// table. A first row made only of `||` cells becomes the header, otherwise
+/// the header is empty. `\\` line breaks become line breaks in the cell, and
+/// pipes inside links (`[text|url]`) and macros (`{color:red}`) are kept.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there is no table.
+///
+/// # Examples
+///
+/// ```
+/// let tables = ftb::jira::parse_tables("||Name||Link||\n|tea|[docs|http://x]|").unwrap();
+/// assert_eq!(tables[0].header, vec!["Name", "Link"]);
+/// assert_eq!(tables[0].rows, vec![vec!["tea", "[docs|http://x]"]]);
+/// ```
+pub fn parse_tables(input: &str) -> Result<Vec<Table>> {
+    let mut tables = Vec::new();
+    let mut rows: Vec<String> = Vec::new();
+
+    for line in input.lines().map(str::trim) {
+        if line.starts_with('|') {
+            rows.push(line.to_string());
+        } else if line.is_empty() {
+            tables.extend(build_table(&std::mem::take(&mut rows)));
+        } else if let Some(row) = rows.last_mut() {
+            row.push('\n');
+            row.push_str(line);
+        }
+    }
+    tables.extend(build_table(&rows));
+
+    if tables.is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+
+    Ok(tables)
+}
+
+/// Converts the first wiki markup table in the input into an aligned
+/// Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if no table can be parsed or formatted.
+pub fn to_markdown(input: &str) -> Result<String> {
+    let tables = parse_tables(input)?;
+    tables[0].to_markdown()
+}
+
+/// Renders a table as Jira wiki markup.
+///
+/// Pipes in cell content are escaped and line breaks become `\\`. A table
+/// whose header is entirely empty is written without a header row.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::Table;
+///
+/// let table = Table::new(
+///     vec!["Name".to_string(), "Note".to_string()],
+///     vec![vec!["a|b".to_string(), "one<br>two".to_string()]],
+/// );
+/// assert_eq!(
+///     ftb::jira::write_table(&table),
+///     "||Name||Note||\n|a\\|b|one\\\\two|\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table) -> String {
+    let num_columns = table.column_count();
+    let mut output = String::new();
+
+    if table.header.iter().any(|cell| !cell.trim().is_empty()) {
+        output.push_str("||");
+        for col_i in 0..num_columns {
+            output.push_str(&escape_cell(
+                table.header.get(col_i).map_or("", String::as_str),
+            ));
+            output.push_str("||");
+        }
+        output.push('\n');
+    }
+
+    for row in &table.rows {
+        output.push('|');
+        for col_i in 0..num_columns {
+            output.push_str(&escape_cell(row.get(col_i).map_or("", String::as_str)));
+            output.push('|');
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Builds a table from the collected row lines, if there are any.
+fn build_table(lines: &[String]) -> Option<Table> {
+    let mut rows: Vec<Vec<(String, bool)>> = lines.iter().map(|line| split_row(line)).collect();
+    if rows.is_empty() {
+        return None;
+    }
+
+    let header = if rows[0].iter().all(|(_, is_header)| *is_header) {
+        rows.remove(0).into_iter().map(|(text, _)| text).collect()
+    } else {
+        let num_columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        vec![String::new(); num_columns]
+    };
+    let rows = rows
+        .into_iter()
+        .map(|row| row.into_iter().map(|(text, _)| text).collect())
+        .collect();
+
+    Some(Table::new(header, rows))
+}
+
+/// Splits a row into its cells, flagging the `||` header cells.
+///
+/// Pipes inside links (`[text|url]`) and macros (`{color:red}`) do not end a
+/// cell, as long as the bracket is closed on the same row.
+fn split_row(line: &str) -> Vec<(String, bool)> {
+    let chars: Vec<char> = line.chars().collect();
+    let matched = matched_brackets(&chars);
+    let mut cells = Vec::new();
+    let mut cell: Option<(String, bool)> = None;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\\' if next == Some('\\') => {
+                i += 1;
+                push_char(&mut cell, '\n');
+            }
+            '\\' if matches!(next, Some('|' | '[' | ']' | '{' | '}')) => {
+                i += 1;
+                push_char(&mut cell, chars[i]);
+            }
+            '|' if depth == 0 => {
+                if let Some((text, is_header)) = cell.take() {
+                    cells.push((trim_lines(&text), is_header));
+                }
+                let is_header = next == Some('|');
+                if is_header {
+                    i += 1;
+                }
+                cell = Some((String::new(), is_header));
+            }
+            '[' | '{' if matched[i] => {
+                depth += 1;
+                push_char(&mut cell, c);
+            }
+            ']' | '}' if matched[i] => {
+                depth = depth.saturating_sub(1);
+                push_char(&mut cell, c);
+            }
+            _ => push_char(&mut cell, c),
+        }
+        i += 1;
+    }
+
+    // The closing pipe is optional
+    if let Some((text, is_header)) = cell {
+        if !text.trim().is_empty() {
+            cells.push((trim_lines(&text), is_header));
+        }
+    }
+
+    cells
+}
+
+/// Flags the brackets and braces that are closed by a matching one on the
+/// same row, skipping those escaped with a backslash.
+fn matched_brackets(chars: &[char]) -> Vec<bool> {
+    let mut matched = vec![false; chars.len()];
+    let mut open: Vec<usize> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' | '{' => open.push(i),
+            c @ (']' | '}') => {
+                let opener = if c == ']' { '[' } else { '{' };
+                if let Some(start) = open.iter().rposition(|&j| chars[j] == opener) {
+                    matched[open[start]] = true;
+                    matched[i] = true;
+                    open.truncate(start);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    matched
+}
+
+fn push_char(cell: &mut Option<(String, bool)>, c: char) {
+    if let Some((text, _)) = cell {
+        text.push(c);
+    }
+}
+
+/// Trims each line of a cell and the cell as a whole.
+fn trim_lines(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escapes pipes, brackets and braces, and turns line breaks into `\\`.
+/// Empty cells get a space so that `||` is not read as a header cell.
+fn escape_cell(cell: &str) -> String {
+    let escaped = cell_lines(cell)
+        .iter()
+        .map(|line| {
+            let mut escaped = String::with_capacity(line.len());
+            for c in line.chars() {
+                if matches!(c, '|' | '[' | ']' | '{' | '}') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join("\\\\");
+
+    if escaped.is_empty() {
+        " ".to_string()
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headerless_table_and_continuation_lines() {
+        let input = "|a|first\nmore|\n|b|c\n\n|x|";
+        let tables = parse_tables(input).expect("Should parse");
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].header, vec!["", ""]);
+        assert_eq!(
+            tables[0].rows,
+            vec![vec!["a", "first\nmore"], vec!["b", "c"]]
+        );
+        assert_eq!(tables[1].rows, vec![vec!["x"]]);
+    }
+
+    #[test]
+    fn test_escapes_and_line_breaks() {
+        let tables = parse_tables("||A||B||\n|x\\|y|one\\\\two|").expect("Should parse");
+
+        assert_eq!(tables[0].rows, vec![vec!["x|y", "one\ntwo"]]);
+        assert_eq!(write_table(&tables[0]), "||A||B||\n|x\\|y|one\\\\two|\n");
+    }
+
+    #[test]
+    fn test_empty_cells_are_not_header_cells() {
+        let table = Table::new(
+            vec!["a".to_string(), "b".to_string()],
+            vec![vec![String::new(), "1".to_string()]],
+        );
+        let output = write_table(&table);
+
+        assert_eq!(output, "||a||b||\n| |1|\n");
+        assert_eq!(parse_tables(&output).expect("Should parse")[0], table);
+    }
+
+    #[test]
+    fn test_unbalanced_bracket_does_not_swallow_row() {
+        let tables =
+            parse_tables("||a||b||\n| [x | y |\n| [l|http://x] | {c} |").expect("Should parse");
+
+        assert_eq!(
+            tables[0].rows,
+            vec![vec!["[x", "y"], vec!["[l|http://x]", "{c}"]]
+        );
+    }
+
+    #[test]
+    fn test_write_escapes_brackets() {
+        let table = Table::new(vec!["a".to_string()], vec![vec!["[x|y] {z}".to_string()]]);
+        let output = write_table(&table);
+
+        assert_eq!(output, "||a||\n|\\[x\\|y\\] \\{z\\}|\n");
+        assert_eq!(parse_tables(&output).expect("Should parse")[0], table);
+    }
+
+    #[test]
+    fn test_no_table_error() {
+        assert!(matches!(
+            parse_tables("just text"),
+            Err(TableError::EmptyInput)
+        ));
+    }
+}
@@ -0,0 +1,386 @@
+//! Conversion between tables and JSON or newline-delimited JSON.
+
+use crate::{ColumnType, Result, Table, TableError};
+use serde_json::{Map, Number, Value};
+
+/// Options controlling how table cells become JSON values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Convert cells to numbers, booleans and `null` based on the inferred
+    /// [`ColumnType`] of their column. When disabled every cell is a string.
+    pub coerce_types: bool,
+}
+
+/// Options controlling how JSON objects become table rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonImportOptions {
+    /// Columns to include, in order. When empty, every key becomes a column,
+    /// ordered by first appearance.
+    pub columns: Vec<String>,
+}
+
+/// Parses a JSON array of objects, a single object, or NDJSON into a [`Table`].
+///
+/// Nested objects are flattened into dotted keys (`owner.name`). Arrays of
+/// scalars are joined with `, `; other arrays are rendered as compact JSON.
+/// `null` becomes an empty cell.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there are no objects, or
+/// `TableError::InvalidStructure` if the input is not valid JSON or contains
+/// values other than objects.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::json::{parse_table, JsonImportOptions};
+///
+/// let input = r#"[{"id": 1, "owner": {"name": "ana"}}, {"id": 2, "tags": ["a", "b"]}]"#;
+/// let table = parse_table(input, &JsonImportOptions::default()).unwrap();
+/// assert_eq!(table.header, vec!["id", "owner.name", "tags"]);
+/// assert_eq!(table.rows[1], vec!["2", "", "a, b"]);
+/// ```
+pub fn parse_table(input: &str, options: &JsonImportOptions) -> Result<Table> {
+    if input.trim().is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+
+    let objects = match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(values)) => values,
+        Ok(value) => vec![value],
+        // NDJSON parses as one complete value followed by more; anything else
+        // is malformed JSON, reported where serde found the problem
+        Err(_) if first_value_parses(input) => parse_ndjson(input)?,
+        Err(e) => return Err(TableError::InvalidStructure(format!("Invalid JSON: {e}"))),
+    };
+
+    let mut keys: Vec<String> = options.columns.clone();
+    let mut records = Vec::with_capacity(objects.len());
+
+    for (i, value) in objects.into_iter().enumerate() {
+        let Value::Object(object) = value else {
+            return Err(TableError::InvalidStructure(format!(
+                "Expected an object at position {}, found {}",
+                i + 1,
+                value_kind(&value)
+            )));
+        };
+
+        let mut record = Map::new();
+        flatten_object(&mut record, "", object);
+        if options.columns.is_empty() {
+            for key in record.keys() {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        records.push(record);
+    }
+
+    if records.is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+
+    let rows = records
+        .iter()
+        .map(|record| {
+            keys.iter()
+                .map(|key| record.get(key).map(cell_text).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    Ok(Table::new(keys, rows))
+}
+
+/// Converts JSON or NDJSON into an aligned Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if the input cannot be parsed or formatted.
+pub fn to_markdown(input: &str, options: &JsonImportOptions) -> Result<String> {
+    parse_table(input, options)?.to_markdown()
+}
+
+/// Whether the input starts with a complete JSON value.
+fn first_value_parses(input: &str) -> bool {
+    serde_json::Deserializer::from_str(input)
+        .into_iter::<Value>()
+        .next()
+        .is_some_and(|value| value.is_ok())
+}
+
+/// Parses one JSON value per non-blank line.
+fn parse_ndjson(input: &str) -> Result<Vec<Value>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                TableError::InvalidStructure(format!("Invalid JSON on line {}: {e}", line_i + 1))
+            })
+        })
+        .collect()
+}
+
+/// Inserts the leaves of `object` into `record`, joining nested keys with dots.
+fn flatten_object(record: &mut Map<String, Value>, prefix: &str, object: Map<String, Value>) {
+    for (key, value) in object {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            Value::Object(nested) if !nested.is_empty() => flatten_object(record, &key, nested),
+            value => {
+                record.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Renders a JSON value as cell text.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) if items.iter().all(is_scalar) => {
+            items.iter().map(cell_text).collect::<Vec<_>>().join(", ")
+        }
+        Value::Object(object) if object.is_empty() => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Converts the body rows of a table into JSON objects keyed by header.
+///
+/// Empty header cells are named `Column N`, and repeated names get a numeric
+/// suffix (`name`, `name_2`) so no value is lost.
+#[must_use]
+pub fn table_to_values(table: &Table, options: &JsonOptions) -> Vec<Value> {
+    let keys = object_keys(table);
+    let types = if options.coerce_types {
+        table.column_types()
+    } else {
+        vec![ColumnType::Text; keys.len()]
+    };
+
+    table
+        .rows
+        .iter()
+        .map(|row| {
+            let object: Map<String, Value> = keys
+                .iter()
+                .zip(&types)
+                .enumerate()
+                .map(|(col_i, (key, column_type))| {
+                    let cell = row.get(col_i).map_or("", String::as_str);
+                    (key.clone(), cell_value(cell, *column_type))
+                })
+                .collect();
+            Value::Object(object)
+        })
+        .collect()
+}
+
+/// Renders a table as a pretty-printed JSON array of objects.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::json::{write_json, JsonOptions};
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| name | qty |\n|-|-|\n| tea | 2 |")
+///     .unwrap();
+/// let json = write_json(&table, &JsonOptions { coerce_types: true });
+/// assert_eq!(json, "[\n  {\n    \"name\": \"tea\",\n    \"qty\": 2\n  }\n]\n");
+/// ```
+#[must_use]
+pub fn write_json(table: &Table, options: &JsonOptions) -> String {
+    let values = Value::Array(table_to_values(table, options));
+    let mut output = serde_json::to_string_pretty(&values).unwrap_or_default();
+    output.push('\n');
+    output
+}
+
+/// Renders a table as newline-delimited JSON, one object per body row.
+#[must_use]
+pub fn write_ndjson(table: &Table, options: &JsonOptions) -> String {
+    table_to_values(table, options)
+        .iter()
+        .map(|value| format!("{value}\n"))
+        .collect()
+}
+
+/// Builds unique object keys from the header.
+fn object_keys(table: &Table) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+
+    for col_i in 0..table.column_count() {
+        let name = match table.header.get(col_i).map(|cell| cell.trim()) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => format!("Column {}", col_i + 1),
+        };
+
+        let mut key = name.clone();
+        let mut suffix = 2;
+        while keys.contains(&key) {
+            key = format!("{name}_{suffix}");
+            suffix += 1;
+        }
+        keys.push(key);
+    }
+
+    keys
+}
+
+/// Converts a single cell according to its column type.
+fn cell_value(cell: &str, column_type: ColumnType) -> Value {
+    let trimmed = cell.trim();
+    if column_type != ColumnType::Text && trimmed.is_empty() {
+        return Value::Null;
+    }
+
+    match column_type {
+        ColumnType::Integer => trimmed
+            .parse::<i64>()
+            .map_or_else(|_| Value::String(cell.to_string()), Value::from),
+        ColumnType::Number => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map_or_else(|| Value::String(cell.to_string()), Value::Number),
+        ColumnType::Boolean => Value::Bool(trimmed.eq_ignore_ascii_case("true")),
+        ColumnType::Text => Value::String(cell.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(header: &[&str], rows: &[&[&str]]) -> Table {
+        let strings = |cells: &[&str]| cells.iter().map(|cell| (*cell).to_string()).collect();
+        Table::new(
+            strings(header),
+            rows.iter().map(|row| strings(row)).collect(),
+        )
+    }
+
+    #[test]
+    fn test_values_without_coercion_are_strings() {
+        let table = table(&["id", "ok"], &[&["1", "true"]]);
+        let values = table_to_values(&table, &JsonOptions::default());
+
+        assert_eq!(values, vec![serde_json::json!({"id": "1", "ok": "true"})]);
+    }
+
+    #[test]
+    fn test_values_with_coercion() {
+        let table = table(
+            &["id", "price", "ok"],
+            &[&["1", "2.5", "TRUE"], &["", "3", "false"]],
+        );
+        let values = table_to_values(&table, &JsonOptions { coerce_types: true });
+
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!({"id": 1, "price": 2.5, "ok": true}),
+                serde_json::json!({"id": null, "price": 3.0, "ok": false}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_and_empty_header_keys() {
+        let table = table(&["a", "a", ""], &[&["1", "2", "3"]]);
+        let values = table_to_values(&table, &JsonOptions::default());
+
+        assert_eq!(
+            values,
+            vec![serde_json::json!({"a": "1", "a_2": "2", "Column 3": "3"})]
+        );
+    }
+
+    #[test]
+    fn test_parse_ndjson_unions_keys() {
+        let input = "{\"a\": 1}\n\n{\"b\": null, \"a\": true}\n";
+        let table = parse_table(input, &JsonImportOptions::default()).expect("Should parse");
+
+        assert_eq!(table.header, vec!["a", "b"]);
+        assert_eq!(table.rows, vec![vec!["1", ""], vec!["true", ""]]);
+    }
+
+    #[test]
+    fn test_parse_with_column_list() {
+        let input = r#"[{"a": 1, "b": {"c": 2}, "d": [[1], 2]}]"#;
+        let options = JsonImportOptions {
+            columns: vec!["d".to_string(), "b.c".to_string(), "missing".to_string()],
+        };
+        let table = parse_table(input, &options).expect("Should parse");
+
+        assert_eq!(table.header, vec!["d", "b.c", "missing"]);
+        assert_eq!(table.rows, vec![vec!["[[1],2]", "2", ""]]);
+    }
+
+    #[test]
+    fn test_parse_rejects_non_objects() {
+        let result = parse_table("[{\"a\": 1}, 2]", &JsonImportOptions::default());
+
+        assert!(matches!(result, Err(TableError::InvalidStructure(_))));
+    }
+
+    #[test]
+    fn test_parse_reports_invalid_ndjson_line() {
+        let result = parse_table("{\"a\": 1}\n{oops\n", &JsonImportOptions::default());
+
+        assert!(
+            matches!(result, Err(TableError::InvalidStructure(msg)) if msg.starts_with("Invalid JSON on line 2"))
+        );
+    }
+
+    #[test]
+    fn test_parse_reports_json_error_position() {
+        let result = parse_table("[{\"a\": 1},\n {\"a\": 2,}]", &JsonImportOptions::default());
+
+        assert_eq!(
+            result,
+            Err(TableError::InvalidStructure(
+                "Invalid JSON: trailing comma at line 2 column 10".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_ndjson_one_line_per_row() {
+        let table = table(&["k"], &[&["x"], &["y"]]);
+
+        assert_eq!(
+            write_ndjson(&table, &JsonOptions::default()),
+            "{\"k\":\"x\"}\n{\"k\":\"y\"}\n"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Rendering of tables as LaTeX `tabular` environments.
+
+use crate::table::{cell_lines, pad_cell};
+use crate::{Alignment, Table};
+use unicode_width::UnicodeWidthStr;
+
+/// Options controlling LaTeX output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatexOptions {
+    /// Use `\toprule`, `\midrule` and `\bottomrule` from the `booktabs`
+    /// package instead of `\hline`.
+    pub booktabs: bool,
+}
+
+/// Renders a table as a LaTeX `tabular` environment.
+///
+/// Column alignment becomes the `l`, `c` and `r` column specifiers, with
+/// unaligned columns set left. The characters LaTeX treats specially are
+/// escaped, and line breaks inside a cell become spaces since plain `l/c/r`
+/// columns cannot break lines.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::latex::LatexOptions;
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Item | Cost |\n|-|-:|\n| tea & cake | 5% |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::latex::write_table(&table, &LatexOptions::default()),
+///     "\\begin{tabular}{lr}\n\\hline\nItem        & Cost \\\\\n\\hline\ntea \\& cake & 5\\%  \\\\\n\\hline\n\\end{tabular}\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table, options: &LatexOptions) -> String {
+    let num_columns = table.column_count();
+    let rows: Vec<Vec<String>> = std::iter::once(&table.header)
+        .chain(&table.rows)
+        .map(|row| {
+            (0..num_columns)
+                .map(|col_i| escape_cell(row.get(col_i).map_or("", String::as_str)))
+                .collect()
+        })
+        .collect();
+
+    let mut widths = vec![0; num_columns];
+    for row in &rows {
+        for (col_i, cell) in row.iter().enumerate() {
+            widths[col_i] = widths[col_i].max(cell.width());
+        }
+    }
+
+    let spec: String = (0..num_columns)
+        .map(|col_i| match table.alignment(col_i) {
+            Alignment::None | Alignment::Left => 'l',
+            Alignment::Center => 'c',
+            Alignment::Right => 'r',
+        })
+        .collect();
+    let (top, mid, bottom) = if options.booktabs {
+        ("\\toprule", "\\midrule", "\\bottomrule")
+    } else {
+        ("\\hline", "\\hline", "\\hline")
+    };
+
+    let mut output = format!("\\begin{{tabular}}{{{spec}}}\n{top}\n");
+    for (row_i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(col_i, cell)| pad_cell(cell, widths[col_i], Alignment::None))
+            .collect();
+        output.push_str(&cells.join(" & "));
+        output.push_str(" \\\\\n");
+        if row_i == 0 {
+            output.push_str(mid);
+            output.push('\n');
+        }
+    }
+    output.push_str(bottom);
+    output.push_str("\n\\end{tabular}\n");
+
+    output
+}
+
+/// Escapes LaTeX special characters and joins cell lines with spaces.
+fn escape_cell(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for (line_i, line) in cell_lines(cell).iter().enumerate() {
+        if line_i > 0 {
+            escaped.push(' ');
+        }
+        for c in line.chars() {
+            match c {
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\\' => escaped.push_str("\\textbackslash{}"),
+                '~' => escaped.push_str("\\textasciitilde{}"),
+                '^' => escaped.push_str("\\textasciicircum{}"),
+                _ => escaped.push(c),
+            }
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_special_characters() {
+        assert_eq!(
+            escape_cell("a_b {x} #1 $2 ~ ^ \\"),
+            "a\\_b \\{x\\} \\#1 \\$2 \\textasciitilde{} \\textasciicircum{} \\textbackslash{}"
+        );
+    }
+
+    #[test]
+    fn test_booktabs_rules_and_alignment() {
+        let mut table = Table::new(
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            vec![vec!["1".to_string(), "two<br>lines".to_string()]],
+        );
+        table.alignments = vec![Alignment::Left, Alignment::Center, Alignment::Right];
+
+        assert_eq!(
+            write_table(&table, &LatexOptions { booktabs: true }),
+            "\\begin{tabular}{lcr}\n\\toprule\na & b         & c \\\\\n\\midrule\n1 & two lines &   \\\\\n\\bottomrule\n\\end{tabular}\n"
+        );
+    }
+}
@@ -1,6 +1,33 @@
 use std::fmt;
+use std::path::Path;
 use unicode_width::UnicodeWidthStr;
 
+pub mod asciidoc;
+pub mod box_drawing;
+pub mod columns;
+pub mod csv;
+mod diagnostic;
+mod edit;
+pub mod grid;
+pub mod html;
+pub mod jira;
+pub mod json;
+pub mod latex;
+pub mod mediawiki;
+pub mod org;
+pub mod rst;
+#[cfg(feature = "sheet")]
+pub mod sheet;
+pub mod sql;
+mod table;
+pub mod typst;
+
+use diagnostic::{ErrorLocation, LocatedError};
+pub use diagnostic::{Severity, SourceSpan, TableDiagnostic};
+pub use edit::{EditedDocument, TableEdit};
+use table::{code_span_len, MARKDOWN_ESCAPED};
+pub use table::{Alignment, ColumnType, Table};
+
 /// Errors that can occur during table formatting.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TableError {
@@ -18,6 +45,9 @@ pub enum TableError {
     /// Invalid table structure
     InvalidStructure(String),
 
+    /// Input uses a construct that a Markdown table cannot express
+    Unsupported(String),
+
     /// Input is empty or contains no table
     EmptyInput,
 }
@@ -42,6 +72,9 @@ impl fmt::Display for TableError {
             TableError::InvalidStructure(msg) => {
                 write!(f, "Invalid table structure: {msg}")
             }
+            TableError::Unsupported(msg) => {
+                write!(f, "Unsupported table construct: {msg}")
+            }
             TableError::EmptyInput => {
                 write!(f, "Input is empty or contains no table")
             }
@@ -54,12 +87,366 @@ impl std::error::Error for TableError {}
 /// Result type for table formatting operations.
 pub type Result<T> = std::result::Result<T, TableError>;
 
+/// Markup language of a document, which decides what table syntax to look for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// Markdown pipe tables.
+    #[default]
+    Markdown,
+
+    /// reStructuredText grid and simple tables.
+    Rst,
+
+    /// Org-mode tables.
+    Org,
+}
+
+impl Dialect {
+    /// Guesses the dialect from a file extension, e.g. `.rst`.
+    ///
+    /// Returns `None` for unknown extensions.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "md" | "markdown" | "mdown" | "mkd" => Some(Dialect::Markdown),
+            "rst" | "rest" => Some(Dialect::Rst),
+            "org" => Some(Dialect::Org),
+            _ => None,
+        }
+    }
+}
+
+/// How to measure the display width of characters whose width depends on
+/// the font, such as `°`, `±` and `…` (East Asian "ambiguous" characters).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WidthPolicy {
+    /// Ambiguous characters take one column, as in most Western fonts.
+    #[default]
+    Standard,
+
+    /// Ambiguous characters take two columns, as in CJK fonts and terminals.
+    Cjk,
+}
+
+impl WidthPolicy {
+    /// Returns the number of columns `text` takes when displayed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::WidthPolicy;
+    ///
+    /// assert_eq!(WidthPolicy::Standard.width("±5°"), 3);
+    /// assert_eq!(WidthPolicy::Cjk.width("±5°"), 5);
+    /// ```
+    #[must_use]
+    pub fn width(self, text: &str) -> usize {
+        match self {
+            WidthPolicy::Standard => text.width(),
+            WidthPolicy::Cjk => text.width_cjk(),
+        }
+    }
+}
+
+/// A table found in a document, together with its formatted text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSpan {
+    /// Index of the first line of the table (0-based).
+    pub start_line: usize,
+
+    /// Index one past the last line of the table.
+    pub end_line: usize,
+
+    /// The formatted table, one line per row, each ending with a newline.
+    pub formatted: String,
+}
+
+impl TableSpan {
+    /// Checks whether the formatted table differs from the original lines.
+    fn is_changed(&self, lines: &[&str]) -> bool {
+        let mut formatted = self.formatted.lines();
+        let unchanged = lines[self.start_line..self.end_line]
+            .iter()
+            .all(|line| formatted.next() == Some(*line));
+        !unchanged || formatted.next().is_some()
+    }
+
+    /// Returns the edit that replaces the table's lines in `document`, the
+    /// document the span was found in, with the formatted table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableFormatter};
+    ///
+    /// let doc = "Intro\n\n| a |\n|-|";
+    /// let spans = TableFormatter::new().check_document_as(doc, Dialect::Markdown);
+    /// let edit = spans[0].edit(doc);
+    /// // Without a final newline in the document, none is added
+    /// assert_eq!((edit.range, edit.new_text.as_str()), (7..16, "| a |\n|---|"));
+    /// ```
+    #[must_use]
+    pub fn edit(&self, document: &str) -> TextEdit {
+        self.edit_at(document, &line_starts(document))
+    }
+
+    /// Like [`TableSpan::edit`], given the byte offset of each line.
+    fn edit_at(&self, document: &str, line_starts: &[usize]) -> TextEdit {
+        let start = line_starts[self.start_line];
+        let mut new_text = with_line_endings_of(&self.formatted, &document[start..]);
+        let (end, end_position) = match line_starts.get(self.end_line) {
+            Some(&end) => (end, TextPosition::new(self.end_line, "")),
+            None => {
+                // The table ends the document without a final newline
+                if new_text.ends_with('\n') {
+                    new_text.pop();
+                }
+                if new_text.ends_with('\r') {
+                    new_text.pop();
+                }
+                let line_start = line_starts[self.end_line - 1];
+                let position = TextPosition::new(self.end_line - 1, &document[line_start..]);
+                (document.len(), position)
+            }
+        };
+
+        TextEdit {
+            range: start..end,
+            start: TextPosition::new(self.start_line, ""),
+            end: end_position,
+            new_text,
+        }
+    }
+}
+
+/// A place in a document, with the column counted both in UTF-8 bytes and in
+/// UTF-16 code units, as the Language Server Protocol counts them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextPosition {
+    /// Line index (0-based).
+    pub line: usize,
+
+    /// Byte offset within the line.
+    pub utf8_column: usize,
+
+    /// UTF-16 code unit offset within the line.
+    pub utf16_column: usize,
+}
+
+impl TextPosition {
+    /// Creates the position at the end of `before`, the start of a line up to the position.
+    fn new(line: usize, before: &str) -> Self {
+        Self {
+            line,
+            utf8_column: before.len(),
+            utf16_column: before.encode_utf16().count(),
+        }
+    }
+}
+
+/// A replacement of part of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The byte range replaced.
+    pub range: std::ops::Range<usize>,
+
+    /// Where the replaced range starts.
+    pub start: TextPosition,
+
+    /// Where the replaced range ends.
+    pub end: TextPosition,
+
+    /// The text put in its place.
+    pub new_text: String,
+}
+
+/// Returns the byte offset at which each line of a document starts, plus the
+/// document's length when it ends with a newline.
+fn line_starts(document: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(document.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// A formatted document, with the tables that had to be left as they were.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedDocument {
+    /// The document with every table that could be formatted formatted.
+    pub text: String,
+
+    /// The tables that could not be formatted, in document order.
+    pub diagnostics: Vec<TableDiagnostic>,
+}
+
+/// The tables of a document: those that were formatted and those that failed.
+#[derive(Debug, Default)]
+pub(crate) struct TableScan {
+    pub(crate) spans: Vec<TableSpan>,
+    pub(crate) diagnostics: Vec<TableDiagnostic>,
+
+    /// Where in its table the error of each diagnostic was raised.
+    locations: Vec<Option<ErrorLocation>>,
+}
+
+impl TableScan {
+    /// Records a formatted table.
+    ///
+    /// Scanning resumes on the line after a failed table, so a table can start
+    /// inside the lines of a failure; the failure is then cut short.
+    pub(crate) fn push_span(&mut self, span: TableSpan) {
+        if let Some(diagnostic) = self.diagnostics.last_mut() {
+            diagnostic.end_line = diagnostic.end_line.min(span.start_line);
+        }
+        self.spans.push(span);
+    }
+
+    /// Records a table on lines `start_line..end_line` that failed, unless it
+    /// is part of the previous failure.
+    pub(crate) fn push_diagnostic(
+        &mut self,
+        start_line: usize,
+        end_line: usize,
+        error: LocatedError,
+    ) {
+        let overlaps = self
+            .diagnostics
+            .last()
+            .is_some_and(|last| start_line < last.end_line);
+        if !overlaps {
+            self.diagnostics
+                .push(TableDiagnostic::new(start_line, end_line, error.error));
+            self.locations.push(error.location);
+        }
+    }
+}
+
+/// A place in a document: a line and a column counted in characters, both 0-based.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Line index.
+    pub line: usize,
+
+    /// Character index within the line.
+    pub column: usize,
+}
+
+/// Moves a cursor column from a table line to the same line once formatted,
+/// keeping it in the same cell at the same offset from the cell's text.
+fn map_cursor_column(original: &str, formatted: &str, column: usize) -> usize {
+    let cell_index = |cells: &[(usize, usize)]| {
+        cells
+            .iter()
+            .rposition(|&(start, _)| start <= column)
+            .unwrap_or(0)
+    };
+    let original_chars: Vec<char> = original.chars().collect();
+    let formatted_chars: Vec<char> = formatted.chars().collect();
+    let original_cells = cell_ranges(&original_chars);
+    let formatted_cells = cell_ranges(&formatted_chars);
+
+    let index = cell_index(&original_cells);
+    let (start, end) = original_cells[index];
+    let offset = column.saturating_sub(text_start(&original_chars, start, end));
+
+    let (start, end) = formatted_cells[index.min(formatted_cells.len() - 1)];
+    (text_start(&formatted_chars, start, end) + offset).min(end)
+}
+
+/// Finds the character ranges of the cells of a table line: the text between
+/// unescaped pipes or, for lines without pipes such as reST simple tables,
+/// the words between gutters of two or more spaces.
+fn cell_ranges(line: &[char]) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+
+    if line.contains(&'|') {
+        let mut start = 0;
+        let mut escaped = false;
+        for (i, &c) in line.iter().enumerate() {
+            if c == '|' && !escaped {
+                cells.push((start, i));
+                start = i + 1;
+            }
+            escaped = c == '\\' && !escaped;
+        }
+        cells.push((start, line.len()));
+    } else {
+        let mut i = 0;
+        while i < line.len() {
+            if line[i] == ' ' {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < line.len() && !(line[i] == ' ' && line.get(i + 1).is_none_or(|&c| c == ' ')) {
+                i += 1;
+            }
+            cells.push((start, i));
+        }
+        if cells.is_empty() {
+            cells.push((0, line.len()));
+        }
+    }
+
+    cells
+}
+
+/// Returns the index of the first non-space character in `start..end`, or
+/// `end` for a blank cell.
+fn text_start(line: &[char], start: usize, end: usize) -> usize {
+    (start..end).find(|&i| line[i] != ' ').unwrap_or(end)
+}
+
+/// Ends the lines of formatted text with `\r\n` if the first line of `rest`,
+/// the original text from the start of the table, ends that way.
+fn with_line_endings_of(formatted: &str, rest: &str) -> String {
+    let first_line = rest.split_inclusive('\n').next().unwrap_or_default();
+    if first_line.ends_with("\r\n") {
+        formatted.replace('\n', "\r\n")
+    } else {
+        formatted.to_string()
+    }
+}
+
+/// Rebuilds a document with each table span replaced by its formatted text.
+///
+/// Lines outside the spans are kept as they are, line endings included, and
+/// a formatted table ends its lines with `\r\n` if its first line did.
+/// `spans` must be in document order and must not overlap.
+pub(crate) fn replace_tables(document: &str, spans: &[TableSpan]) -> String {
+    let lines: Vec<&str> = document.split_inclusive('\n').collect();
+    let mut output = String::with_capacity(document.len() + 1024);
+    let mut i = 0;
+
+    for span in spans {
+        output.extend(lines[i..span.start_line].iter().copied());
+        output.push_str(&with_line_endings_of(
+            &span.formatted,
+            lines[span.start_line],
+        ));
+        i = span.end_line;
+    }
+    output.extend(lines[i..].iter().copied());
+
+    // Remove trailing newline if original didn't have one
+    if !document.ends_with('\n') && output.ends_with('\n') {
+        output.pop();
+        if output.ends_with('\r') {
+            output.pop();
+        }
+    }
+
+    output
+}
+
 /// A Markdown table formatter that aligns columns properly.
 ///
 /// This is a port of the JavaScript formatter from <http://markdowntable.com/>
 pub struct TableFormatter {
     cells: Vec<Vec<String>>,
     column_widths: Vec<usize>,
+    alignments: Vec<Alignment>,
+    width_policy: WidthPolicy,
 }
 
 impl TableFormatter {
@@ -69,9 +456,28 @@ impl TableFormatter {
         Self {
             cells: Vec::new(),
             column_widths: Vec::new(),
+            alignments: Vec::new(),
+            width_policy: WidthPolicy::default(),
         }
     }
 
+    /// Sets how the display width of Markdown table cells is measured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{TableFormatter, WidthPolicy};
+    ///
+    /// let mut formatter = TableFormatter::new().with_width_policy(WidthPolicy::Cjk);
+    /// let output = formatter.format_table("| ° | a |\n|-|-|").unwrap();
+    /// assert_eq!(output, "| ° | a |\n|----|---|\n");
+    /// ```
+    #[must_use]
+    pub fn with_width_policy(mut self, width_policy: WidthPolicy) -> Self {
+        self.width_policy = width_policy;
+        self
+    }
+
     /// Formats tables within a Markdown document, preserving all other content.
     ///
     /// This method scans the document for tables and formats them in place while
@@ -90,73 +496,352 @@ impl TableFormatter {
     /// assert!(output.contains("| a | b |"));
     /// ```
     pub fn format_document(&mut self, document: &str) -> String {
-        let mut output = String::with_capacity(document.len() + 1024);
+        let spans = self.scan_markdown_tables(document).spans;
+        replace_tables(document, &spans)
+    }
+
+    /// Formats tables within a document written in the given dialect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableFormatter};
+    ///
+    /// let doc = "+-+\n| abc |\n+-+\n";
+    /// let output = TableFormatter::new().format_document_as(doc, Dialect::Rst);
+    /// assert_eq!(output, "+-----+\n| abc |\n+-----+\n");
+    /// ```
+    pub fn format_document_as(&mut self, document: &str, dialect: Dialect) -> String {
+        match dialect {
+            Dialect::Markdown => self.format_document(document),
+            Dialect::Rst => rst::format_document(document),
+            Dialect::Org => org::format_document(document),
+        }
+    }
+
+    /// Formats tables within a document written in the given dialect and
+    /// reports the tables that were skipped because they could not be
+    /// formatted, instead of leaving them unchanged silently.
+    ///
+    /// The text is the same as [`TableFormatter::format_document_as`] returns,
+    /// and the diagnostics are those of [`TableFormatter::diagnose_document_as`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableError, TableFormatter};
+    ///
+    /// let doc = "| a |\n|-|\n\n| b |\n| c |\n";
+    /// let formatted = TableFormatter::new().format_document_with_diagnostics_as(doc, Dialect::Markdown);
+    /// assert_eq!(formatted.text, "| a |\n|---|\n\n| b |\n| c |\n");
+    /// assert_eq!(formatted.diagnostics.len(), 1);
+    /// assert_eq!(formatted.diagnostics[0].start_line, 3);
+    /// ```
+    pub fn format_document_with_diagnostics_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+    ) -> FormattedDocument {
+        let scan = self.scan_tables(document, dialect);
+        FormattedDocument {
+            text: replace_tables(document, &scan.spans),
+            diagnostics: scan.diagnostics,
+        }
+    }
+
+    /// Finds the tables that formatting would change, in document order.
+    ///
+    /// An empty result means the document is already formatted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableFormatter};
+    ///
+    /// let doc = "| a | b |\n|---|---|\n\ntext\n\n| a | b |\n|-|-|\n";
+    /// let changes = TableFormatter::new().check_document_as(doc, Dialect::Markdown);
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!((changes[0].start_line, changes[0].end_line), (5, 7));
+    /// ```
+    pub fn check_document_as(&mut self, document: &str, dialect: Dialect) -> Vec<TableSpan> {
+        self.check_document_with_diagnostics_as(document, dialect).0
+    }
+
+    /// Finds the tables that formatting would change along with the tables
+    /// that cannot be formatted, from a single scan of the document.
+    ///
+    /// The changes are those of [`TableFormatter::check_document_as`] and the
+    /// diagnostics those of [`TableFormatter::diagnose_document_as`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableFormatter};
+    ///
+    /// let doc = "| a |\n|-|\n\n| b |\n| c |\n";
+    /// let (changes, diagnostics) =
+    ///     TableFormatter::new().check_document_with_diagnostics_as(doc, Dialect::Markdown);
+    /// assert_eq!((changes.len(), diagnostics.len()), (1, 1));
+    /// ```
+    pub fn check_document_with_diagnostics_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+    ) -> (Vec<TableSpan>, Vec<TableDiagnostic>) {
+        let scan = self.scan_tables(document, dialect);
+        let lines: Vec<&str> = document.lines().collect();
+        let changes = scan
+            .spans
+            .into_iter()
+            .filter(|span| span.is_changed(&lines))
+            .collect();
+
+        (changes, scan.diagnostics)
+    }
+
+    /// Returns one edit per Markdown table that formatting would change, in
+    /// document order, leaving everything else untouched.
+    ///
+    /// Applying the edits from last to first gives the same text as
+    /// [`TableFormatter::format_document`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::TableFormatter;
+    ///
+    /// let doc = "| é |\n|---|\n\n| a | b |\n|-|-|\n";
+    /// let edits = TableFormatter::new().format_document_edits(doc);
+    /// assert_eq!(edits.len(), 1);
+    /// assert_eq!(edits[0].range, 14..30);
+    /// assert_eq!((edits[0].start.line, edits[0].end.line), (3, 5));
+    /// assert_eq!(edits[0].new_text, "| a | b |\n|---|---|\n");
+    /// ```
+    pub fn format_document_edits(&mut self, document: &str) -> Vec<TextEdit> {
+        self.format_document_edits_as(document, Dialect::Markdown)
+    }
+
+    /// Returns one edit per table that formatting would change, for a
+    /// document written in the given dialect.
+    pub fn format_document_edits_as(&mut self, document: &str, dialect: Dialect) -> Vec<TextEdit> {
+        let line_starts = line_starts(document);
+        self.check_document_as(document, dialect)
+            .iter()
+            .map(|span| span.edit_at(document, &line_starts))
+            .collect()
+    }
+
+    /// Formats only the tables that overlap a range of lines (0-based, end
+    /// exclusive), preserving everything else.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableFormatter};
+    ///
+    /// let doc = "| a |\n|-|\n\n| b |\n|-|\n";
+    /// let output = TableFormatter::new().format_range_as(doc, Dialect::Markdown, 3..4);
+    /// assert_eq!(output, "| a |\n|-|\n\n| b |\n|---|\n");
+    /// ```
+    pub fn format_range_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+        lines: std::ops::Range<usize>,
+    ) -> String {
+        self.format_range_with_diagnostics_as(document, dialect, lines)
+            .text
+    }
+
+    /// Formats the tables that overlap a range of lines like
+    /// [`TableFormatter::format_range_as`], and reports the tables of the
+    /// whole document that could not be formatted, from the same scan.
+    pub fn format_range_with_diagnostics_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+        lines: std::ops::Range<usize>,
+    ) -> FormattedDocument {
+        let scan = self.scan_tables(document, dialect);
+        let spans: Vec<TableSpan> = scan
+            .spans
+            .into_iter()
+            .filter(|span| span.start_line < lines.end && lines.start < span.end_line)
+            .collect();
+
+        FormattedDocument {
+            text: replace_tables(document, &spans),
+            diagnostics: scan.diagnostics,
+        }
+    }
+
+    /// Formats only the table containing `cursor` and returns the document
+    /// with the cursor moved so it stays at the same place in the same cell.
+    ///
+    /// The document and cursor are returned unchanged when the cursor is not
+    /// inside a table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, Position, TableFormatter};
+    ///
+    /// let doc = "| a | b |\n|-|-|\n| long | x |\n";
+    /// // The cursor is on the `b` of the header
+    /// let cursor = Position { line: 0, column: 6 };
+    /// let (output, cursor) = TableFormatter::new().format_at_cursor_as(doc, Dialect::Markdown, cursor);
+    /// assert_eq!(output, "| a    | b |\n|------|---|\n| long | x |\n");
+    /// assert_eq!(cursor, Position { line: 0, column: 9 });
+    /// ```
+    pub fn format_at_cursor_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+        cursor: Position,
+    ) -> (String, Position) {
+        let (formatted, cursor) =
+            self.format_at_cursor_with_diagnostics_as(document, dialect, cursor);
+        (formatted.text, cursor)
+    }
+
+    /// Formats the table containing `cursor` like
+    /// [`TableFormatter::format_at_cursor_as`], and reports the tables of the
+    /// whole document that could not be formatted, from the same scan.
+    pub fn format_at_cursor_with_diagnostics_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+        cursor: Position,
+    ) -> (FormattedDocument, Position) {
+        let scan = self.scan_tables(document, dialect);
+        let diagnostics = scan.diagnostics;
+        let span = scan
+            .spans
+            .into_iter()
+            .find(|span| span.start_line <= cursor.line && cursor.line < span.end_line);
+        let Some(span) = span else {
+            let text = document.to_string();
+            return (FormattedDocument { text, diagnostics }, cursor);
+        };
+
         let lines: Vec<&str> = document.lines().collect();
+        let formatted: Vec<&str> = span.formatted.lines().collect();
+        // Rows keep their lines unless formatting dropped some
+        let row = (cursor.line - span.start_line).min(formatted.len().saturating_sub(1));
+        let column = map_cursor_column(
+            lines[cursor.line],
+            formatted.get(row).copied().unwrap_or_default(),
+            cursor.column,
+        );
+
+        let text = replace_tables(document, std::slice::from_ref(&span));
+        let cursor = Position {
+            line: span.start_line + row,
+            column,
+        };
+        (FormattedDocument { text, diagnostics }, cursor)
+    }
+
+    /// Finds the tables that could not be formatted and are left as they are.
+    ///
+    /// In Markdown, only blocks whose first line starts with `|` are reported,
+    /// so prose containing a pipe is not mistaken for a broken table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Dialect, TableError, TableFormatter};
+    ///
+    /// let doc = "| a | b |\n| 1 | 2 |\n";
+    /// let diagnostics = TableFormatter::new().diagnose_document_as(doc, Dialect::Markdown);
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!((diagnostics[0].start_line, diagnostics[0].end_line), (0, 2));
+    /// assert!(matches!(diagnostics[0].error, TableError::InvalidStructure(_)));
+    /// ```
+    pub fn diagnose_document_as(
+        &mut self,
+        document: &str,
+        dialect: Dialect,
+    ) -> Vec<TableDiagnostic> {
+        self.scan_tables(document, dialect).diagnostics
+    }
+
+    /// Finds every formattable table in a document written in the given dialect.
+    pub(crate) fn table_spans(&mut self, document: &str, dialect: Dialect) -> Vec<TableSpan> {
+        self.scan_tables(document, dialect).spans
+    }
+
+    /// Finds the tables in a document, formatted or with the reason they could not be.
+    fn scan_tables(&mut self, document: &str, dialect: Dialect) -> TableScan {
+        let mut scan = match dialect {
+            Dialect::Markdown => self.scan_markdown_tables(document),
+            Dialect::Rst => rst::scan_tables(document),
+            Dialect::Org => org::scan_tables(document),
+        };
+        diagnostic::locate(document, &mut scan.diagnostics, &scan.locations);
+        scan
+    }
+
+    /// Finds the Markdown tables in a document along with their formatted text.
+    fn scan_markdown_tables(&mut self, document: &str) -> TableScan {
+        let lines: Vec<&str> = document.lines().collect();
+        let mut scan = TableScan::default();
         let mut i = 0;
 
         while i < lines.len() {
-            let line = lines[i];
-
             // Check if this line starts a table (contains '|')
-            if line.trim().starts_with('|')
-                || (line.contains('|') && !line.trim().starts_with("```"))
-            {
+            if is_table_start(lines[i]) {
                 // Try to extract and format the table
-                if let Some((table_lines, formatted)) = self.try_format_table_at(&lines, i) {
-                    output.push_str(&formatted);
-                    i += table_lines;
-                    continue;
+                match self.try_format_table_at(&lines, i) {
+                    Ok((table_lines, formatted)) => {
+                        scan.push_span(TableSpan {
+                            start_line: i,
+                            end_line: i + table_lines,
+                            formatted,
+                        });
+                        i += table_lines;
+                        continue;
+                    }
+                    // Prose that merely contains a pipe is not a broken table
+                    Err(error) if lines[i].trim_start().starts_with('|') => {
+                        scan.push_diagnostic(i, table_end(&lines, i), error);
+                    }
+                    Err(_) => {}
                 }
             }
-
-            // Not a table or table formatting failed - preserve the line as-is
-            output.push_str(line);
-            output.push('\n');
             i += 1;
         }
 
-        // Remove trailing newline if original didn't have one
-        if !document.ends_with('\n') && output.ends_with('\n') {
-            output.pop();
-        }
-
-        output
+        scan
     }
 
     /// Attempts to extract and format a table starting at the given line index.
     ///
-    /// Returns `Some((num_lines, formatted_table))` if a valid table was found and formatted,
-    /// where `num_lines` is the number of lines consumed from the input.
-    fn try_format_table_at(&mut self, lines: &[&str], start: usize) -> Option<(usize, String)> {
+    /// Returns `Ok((num_lines, formatted_table))` if a valid table was found and formatted,
+    /// where `num_lines` is the number of lines consumed from the input, or the
+    /// reason the lines could not be formatted as a table.
+    fn try_format_table_at(
+        &mut self,
+        lines: &[&str],
+        start: usize,
+    ) -> std::result::Result<(usize, String), LocatedError> {
         if start >= lines.len() {
-            return None;
+            return Err(TableError::EmptyInput.into());
         }
 
         // Scan forward to find the end of the table
-        let mut end = start;
-        while end < lines.len() && lines[end].contains('|') {
-            end += 1;
-        }
+        let end = table_end(lines, start);
 
         if end == start {
-            return None;
+            return Err(TableError::EmptyInput.into());
         }
 
         // Extract table lines
         let table_text = lines[start..end].join("\n");
 
         // Try to format the table
-        match self.format_table(&table_text) {
-            Ok(formatted) => {
-                let num_lines = end - start;
-                Some((num_lines, formatted))
-            }
-            Err(_) => {
-                // If formatting fails, this might not be a valid table
-                // Return None to preserve it as-is
-                None
-            }
-        }
+        let formatted = self.format_located_table(&table_text)?;
+        Ok((end - start, formatted))
     }
 
     /// Formats a markdown table string, returning the aligned version.
@@ -179,6 +864,94 @@ impl TableFormatter {
     /// assert!(result.is_ok());
     /// ```
     pub fn format_table(&mut self, table: &str) -> Result<String> {
+        Ok(self.format_located_table(table)?)
+    }
+
+    /// Formats a markdown table string, keeping the location of any error.
+    fn format_located_table(&mut self, table: &str) -> std::result::Result<String, LocatedError> {
+        self.load_table(table)?;
+
+        // Process table
+        self.get_column_widths();
+        self.add_missing_cell_columns();
+        self.pad_cells_for_output();
+
+        // Render output
+        Ok(self.render_output())
+    }
+
+    /// Parses a markdown table string into a [`Table`] without formatting it.
+    ///
+    /// Backslash escapes such as `\|` or `\*` are removed and short rows are
+    /// padded with empty cells so every row has as many cells as the widest row.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TableError` under the same conditions as [`TableFormatter::format_table`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::TableFormatter;
+    ///
+    /// let mut formatter = TableFormatter::new();
+    /// let table = formatter.parse_table("| a | b |\n|-|-|\n| 1 |").unwrap();
+    /// assert_eq!(table.header, vec!["a", "b"]);
+    /// assert_eq!(table.rows, vec![vec!["1", ""]]);
+    /// ```
+    pub fn parse_table(&mut self, table: &str) -> Result<Table> {
+        self.load_table(table)?;
+        self.get_column_widths();
+        self.add_missing_cell_columns();
+
+        let mut rows = self
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|cell| unescape_cell(cell)).collect());
+        let header = rows.next().unwrap_or_default();
+        let rows = rows.skip(1).collect();
+
+        let mut table = Table::new(header, rows);
+        table.alignments = self.alignments.clone();
+        Ok(table)
+    }
+
+    /// Parses every table in a Markdown document, in document order.
+    ///
+    /// Tables that cannot be parsed are skipped, mirroring [`TableFormatter::format_document`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::TableFormatter;
+    ///
+    /// let doc = "| a |\n|-|\n| 1 |\n\ntext\n\n| b |\n|-|\n| 2 |\n";
+    /// let tables = TableFormatter::new().parse_document(doc);
+    /// assert_eq!(tables.len(), 2);
+    /// assert_eq!(tables[1].rows, vec![vec!["2"]]);
+    /// ```
+    pub fn parse_document(&mut self, document: &str) -> Vec<Table> {
+        let lines: Vec<&str> = document.lines().collect();
+        let mut tables = Vec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            if is_table_start(lines[i]) {
+                let end = table_end(&lines, i);
+                if let Ok(table) = self.parse_table(&lines[i..end].join("\n")) {
+                    tables.push(table);
+                    i = end;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+
+        tables
+    }
+
+    /// Imports a table and validates its size and separator row.
+    fn load_table(&mut self, table: &str) -> std::result::Result<(), LocatedError> {
         const MAX_ROWS: usize = 100_000;
         const MAX_COLS: usize = 1_000;
         const MAX_CELLS: usize = 1_000_000;
@@ -186,10 +959,11 @@ impl TableFormatter {
         // Reset state to allow formatter reuse
         self.cells.clear();
         self.column_widths.clear();
+        self.alignments.clear();
 
         // Check for empty input
         if table.trim().is_empty() {
-            return Err(TableError::EmptyInput);
+            return Err(TableError::EmptyInput.into());
         }
 
         // Import and parse table
@@ -197,7 +971,7 @@ impl TableFormatter {
 
         // Validate minimum structure
         if self.cells.len() < 2 {
-            return Err(TableError::MissingSeparator);
+            return Err(TableError::MissingSeparator.into());
         }
 
         // Validate maximum size
@@ -211,26 +985,31 @@ impl TableFormatter {
                 cols: num_cols,
                 max_rows: MAX_ROWS,
                 max_cols: MAX_COLS,
-            });
+            }
+            .into());
         }
 
         // Validate separator row (row 1)
         if !self.is_separator_row(1) {
-            return Err(TableError::InvalidStructure(
-                "Row 2 is not a valid separator row".to_string(),
+            return Err(LocatedError::at_separator(
+                TableError::InvalidStructure("Row 2 is not a valid separator row".to_string()),
+                1,
+                table.lines().nth(1).unwrap_or_default(),
             ));
         }
 
-        // Process table
-        self.get_column_widths();
-        self.add_missing_cell_columns();
-        self.pad_cells_for_output();
+        // Remember column alignment, then reduce separator cells to plain dashes
+        for cell in &mut self.cells[1] {
+            self.alignments
+                .push(Alignment::from_separator(cell).unwrap_or_default());
+            *cell = "-".to_string();
+        }
 
-        // Render output
-        Ok(self.render_output())
+        Ok(())
     }
 
-    /// Checks if a row is a valid separator row (all cells are dashes).
+    /// Checks if a row is a valid separator row (all cells are dashes, optionally
+    /// with alignment colons at either end).
     fn is_separator_row(&self, row_index: usize) -> bool {
         if row_index >= self.cells.len() {
             return false;
@@ -242,7 +1021,7 @@ impl TableFormatter {
         }
 
         row.iter()
-            .all(|cell| !cell.is_empty() && cell.chars().all(|c| c == '-'))
+            .all(|cell| Alignment::from_separator(cell).is_some())
     }
 
     /// Renders the formatted table to a string.
@@ -268,11 +1047,16 @@ impl TableFormatter {
             output.push_str(" |\n");
         }
 
-        // Separator
+        // Separator, with alignment colons in place of the outer dashes
         if self.cells.len() > 1 {
-            output.push_str("|-");
-            output.push_str(&self.cells[1].join("-|-"));
-            output.push_str("-|\n");
+            for (col_i, cell) in self.cells[1].iter().enumerate() {
+                let alignment = self.alignments.get(col_i).copied().unwrap_or_default();
+                output.push('|');
+                output.push(if alignment.is_left() { ':' } else { '-' });
+                output.push_str(cell);
+                output.push(if alignment.is_right() { ':' } else { '-' });
+            }
+            output.push_str("|\n");
         }
 
         // Data rows
@@ -305,14 +1089,16 @@ impl TableFormatter {
                 continue;
             }
 
-            let row_columns: Vec<String> = row
-                .split('|')
+            let row_columns: Vec<String> = split_row(row)
+                .into_iter()
                 .map(|cell| {
                     let mut trimmed = cell.trim().to_string();
 
-                    // If it's the separator row, normalize to single dash
-                    if row_i == 1 && trimmed.chars().all(|c| c == '-') && !trimmed.is_empty() {
-                        trimmed = "-".to_string();
+                    // If it's the separator row, normalize to a single dash plus any colons
+                    if row_i == 1 {
+                        if let Some(alignment) = Alignment::from_separator(&trimmed) {
+                            trimmed = alignment.separator_cell().to_string();
+                        }
                     }
 
                     trimmed
@@ -375,18 +1161,7 @@ impl TableFormatter {
 
     /// Calculates the maximum width needed for each column.
     fn get_column_widths(&mut self) {
-        self.column_widths.clear();
-
-        for row in &self.cells {
-            for (col_i, cell) in row.iter().enumerate() {
-                let cell_width = cell.width();
-                if col_i >= self.column_widths.len() {
-                    self.column_widths.push(cell_width);
-                } else if self.column_widths[col_i] < cell_width {
-                    self.column_widths[col_i] = cell_width;
-                }
-            }
-        }
+        self.column_widths = column_widths(&self.cells, self.width_policy);
     }
 
     /// Adds missing cells to rows that don't have enough columns.
@@ -405,7 +1180,7 @@ impl TableFormatter {
         for (row_i, row) in self.cells.iter_mut().enumerate() {
             for (col_i, cell) in row.iter_mut().enumerate() {
                 let target_width = self.column_widths[col_i];
-                let current_width = cell.width();
+                let current_width = self.width_policy.width(cell);
                 let padding = target_width.saturating_sub(current_width);
 
                 if padding == 0 {
@@ -426,6 +1201,127 @@ impl TableFormatter {
     }
 }
 
+/// Calculates the maximum display width of each column across rows of
+/// possibly different lengths.
+pub(crate) fn column_widths<R, S>(rows: &[R], width_policy: WidthPolicy) -> Vec<usize>
+where
+    R: AsRef<[S]>,
+    S: AsRef<str>,
+{
+    let mut widths: Vec<usize> = Vec::new();
+
+    for row in rows {
+        for (col_i, cell) in row.as_ref().iter().enumerate() {
+            let cell_width = width_policy.width(cell.as_ref());
+            if col_i >= widths.len() {
+                widths.push(cell_width);
+            } else if widths[col_i] < cell_width {
+                widths[col_i] = cell_width;
+            }
+        }
+    }
+
+    widths
+}
+
+/// Checks if a line could start a table.
+fn is_table_start(line: &str) -> bool {
+    line.trim().starts_with('|') || (line.contains('|') && !line.trim().starts_with("```"))
+}
+
+/// Returns the index one past the last line of the table starting at `start`.
+fn table_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start;
+    while end < lines.len() && lines[end].contains('|') {
+        end += 1;
+    }
+    end
+}
+
+/// Removes the backslash escapes that `Table::to_markdown` adds to a cell.
+///
+/// The backslashes before an escaped character come in pairs, each standing
+/// for one backslash, plus the one escaping the character; other backslashes
+/// are kept. Inside code spans only pipes are unescaped.
+fn unescape_cell(cell: &str) -> String {
+    let mut output = String::with_capacity(cell.len());
+    let mut rest = cell;
+    let mut backslashes = 0;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' {
+            backslashes += 1;
+            rest = &rest[1..];
+            continue;
+        }
+
+        let escaped = MARKDOWN_ESCAPED.contains(&c);
+        let kept = if escaped {
+            backslashes / 2
+        } else {
+            backslashes
+        };
+        output.extend(std::iter::repeat_n('\\', kept));
+
+        match code_span_len(rest).filter(|_| !escaped || backslashes % 2 == 0) {
+            Some(len) => {
+                output.push_str(&unescape_pipes(&rest[..len]));
+                rest = &rest[len..];
+            }
+            None => {
+                output.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        backslashes = 0;
+    }
+    output.extend(std::iter::repeat_n('\\', backslashes));
+
+    output
+}
+
+/// Unescapes `\|` inside a code span, where other backslashes are literal.
+fn unescape_pipes(span: &str) -> String {
+    let mut output = String::with_capacity(span.len());
+    let mut backslashes = 0;
+
+    for c in span.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        let kept = if c == '|' {
+            backslashes / 2
+        } else {
+            backslashes
+        };
+        output.extend(std::iter::repeat_n('\\', kept));
+        output.push(c);
+        backslashes = 0;
+    }
+    output.extend(std::iter::repeat_n('\\', backslashes));
+
+    output
+}
+
+/// Splits a table row on `|`, leaving backslash-escaped pipes (`\\|`) inside cells.
+fn split_row(row: &str) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in row.char_indices() {
+        if c == '|' && !escaped {
+            cells.push(&row[start..i]);
+            start = i + 1;
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells.push(&row[start..]);
+
+    cells
+}
+
 impl Default for TableFormatter {
     fn default() -> Self {
         Self::new()
@@ -478,6 +1374,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_escaped_pipes_stay_in_cell() {
+        let input = "| a | b |\n|-|-|\n| x \\| y | z |\n";
+        let mut formatter = TableFormatter::new();
+        let output = formatter
+            .format_table(input)
+            .expect("Should format successfully");
+
+        assert_eq!(output, "| a      | b |\n|--------|---|\n| x \\| y | z |\n");
+    }
+
+    #[test]
+    fn test_parse_table_unescapes_pipes() {
+        let input = "| a | b |\n|-|-|\n| x \\| y |\n";
+        let mut formatter = TableFormatter::new();
+        let table = formatter
+            .parse_table(input)
+            .expect("Should parse successfully");
+
+        assert_eq!(table.header, vec!["a", "b"]);
+        assert_eq!(table.rows, vec![vec!["x | y", ""]]);
+    }
+
+    #[test]
+    fn test_alignment_colons_preserved() {
+        let input = "| left | center | right | none |\n|:-|:--:|-:|---|\n| a | b | c | d |\n";
+        let mut formatter = TableFormatter::new();
+        let output = formatter
+            .format_table(input)
+            .expect("Should format successfully");
+
+        assert_eq!(
+            output,
+            "| left | center | right | none |\n|:-----|:------:|------:|------|\n| a    | b      | c     | d    |\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid_alignment_separator_error() {
+        let input = "| a |\n|:|\n| b |\n";
+        let mut formatter = TableFormatter::new();
+        let result = formatter.format_table(input);
+
+        assert!(matches!(result, Err(TableError::InvalidStructure(_))));
+    }
+
     #[test]
     fn test_empty_table() {
         let input = "";
@@ -744,4 +1686,142 @@ mod tests {
         assert!(output.contains("| incomplete | table"));
         assert!(output.contains("More text."));
     }
+
+    #[test]
+    fn test_format_document_keeps_crlf_line_endings() {
+        let input = "Intro\r\n\r\n| a | b |\r\n|-|-|\r\n| 1 | 2 |\r\nMixed\nend\r\n";
+        let mut formatter = TableFormatter::new();
+
+        assert_eq!(
+            formatter.format_document(input),
+            "Intro\r\n\r\n| a | b |\r\n|---|---|\r\n| 1 | 2 |\r\nMixed\nend\r\n"
+        );
+        assert_eq!(formatter.format_document("| a |\r\n|-|"), "| a |\r\n|---|");
+        assert_eq!(
+            formatter.format_document_edits("| a |\r\n|-|\r\n")[0].new_text,
+            "| a |\r\n|---|\r\n"
+        );
+    }
+
+    #[test]
+    fn test_check_document_reports_changed_tables() {
+        let doc = "| a | b |\n|---|---|\n\n| long | x |\n|-|-|\n| 1 | 2 |";
+        let changes = TableFormatter::new().check_document_as(doc, Dialect::Markdown);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].start_line, changes[0].end_line), (3, 6));
+        assert_eq!(
+            changes[0].formatted,
+            "| long | x |\n|------|---|\n| 1    | 2 |\n"
+        );
+    }
+
+    #[test]
+    fn test_format_range_reports_diagnostics_of_whole_document() {
+        let doc = "| a |\n|-|\n\n| b |\n| c |\n";
+        let mut formatter = TableFormatter::new();
+
+        let formatted = formatter.format_range_with_diagnostics_as(doc, Dialect::Markdown, 0..1);
+        assert_eq!(formatted.text, "| a |\n|---|\n\n| b |\n| c |\n");
+        assert_eq!(formatted.diagnostics.len(), 1);
+        assert_eq!(formatted.diagnostics[0].start_line, 3);
+
+        let (formatted, _) = formatter.format_at_cursor_with_diagnostics_as(
+            doc,
+            Dialect::Markdown,
+            Position { line: 4, column: 0 },
+        );
+        assert_eq!(formatted.text, doc);
+        assert_eq!(formatted.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_format_at_cursor_keeps_cell() {
+        let doc = "text\n\n| a | b |\n|-|-|\n| long cell | x |\n\n| c |\n|-|\n";
+        let mut formatter = TableFormatter::new();
+
+        // On the `b` of the header
+        let (output, cursor) =
+            formatter.format_at_cursor_as(doc, Dialect::Markdown, Position { line: 2, column: 6 });
+        assert_eq!(
+            output,
+            "text\n\n| a         | b |\n|-----------|---|\n| long cell | x |\n\n| c |\n|-|\n"
+        );
+        assert_eq!(
+            cursor,
+            Position {
+                line: 2,
+                column: 14
+            }
+        );
+
+        // Outside any table nothing changes
+        let (output, cursor) =
+            formatter.format_at_cursor_as(doc, Dialect::Markdown, Position { line: 0, column: 2 });
+        assert_eq!(output, doc);
+        assert_eq!(cursor, Position { line: 0, column: 2 });
+    }
+
+    #[test]
+    fn test_map_cursor_column() {
+        // Trailing padding after the cell text is clamped to the new cell
+        assert_eq!(map_cursor_column("| ab     | c |", "| ab | c |", 7), 5);
+        // reST simple table words separated by gutters
+        assert_eq!(map_cursor_column("a  long text", "a     long text", 8), 11);
+    }
+
+    #[test]
+    fn test_check_document_other_dialects() {
+        let rst = "+---+\n| abc |\n+---+\n";
+        let org = "| a |\n|---|\n| b |\n";
+        let mut formatter = TableFormatter::new();
+
+        assert_eq!(formatter.check_document_as(rst, Dialect::Rst).len(), 1);
+        assert!(formatter.check_document_as(org, Dialect::Org).is_empty());
+    }
+
+    #[test]
+    fn test_format_document_edits_apply() {
+        let doc = "Ünïcode — text\n\n| 日本 | b |\n|-|-|\n\n| ok |\n|----|\n\n| x |\n|-|";
+        let edits = TableFormatter::new().format_document_edits(doc);
+        assert_eq!(edits.len(), 2);
+
+        let first = &edits[0];
+        assert_eq!(first.start, TextPosition::new(2, ""));
+        assert_eq!(first.end, TextPosition::new(4, ""));
+        assert_eq!(&doc[first.range.clone()], "| 日本 | b |\n|-|-|\n");
+
+        // The last table has no final newline, so the edit ends on its last line
+        let last = &edits[1];
+        assert_eq!(
+            last.end,
+            TextPosition {
+                line: 9,
+                utf8_column: 3,
+                utf16_column: 3
+            }
+        );
+
+        let mut output = doc.to_string();
+        for edit in edits.iter().rev() {
+            output.replace_range(edit.range.clone(), &edit.new_text);
+        }
+        assert_eq!(output, TableFormatter::new().format_document(doc));
+    }
+
+    #[test]
+    fn test_text_position_counts_utf16() {
+        let position = TextPosition::new(0, "a😀é");
+        assert_eq!((position.utf8_column, position.utf16_column), (7, 4));
+    }
+
+    #[test]
+    fn test_diagnose_document() {
+        let doc = "Use a | b here.\n\n| a | b |\n| 1 | 2 |\n\n| c |\n|---|\n";
+        let diagnostics = TableFormatter::new().diagnose_document_as(doc, Dialect::Markdown);
+
+        // The prose line is not a table and the valid table is not reported
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].start_line, diagnostics[0].end_line), (2, 4));
+    }
 }
@@ -0,0 +1,603 @@
+//! A Language Server Protocol server over stdio (`ftb lsp`).
+//!
+//! Supports whole-document, range and on-type formatting, publishes a
+//! diagnostic for each table that cannot be formatted, and offers code
+//! actions to format a table or add a missing separator row. Settings come
+//! from the `.ftb.toml` that applies to each document.
+
+use crate::config::ConfigCache;
+use crate::{document_dialect, formatter};
+use ftb::{Dialect, Severity, TableDiagnostic, TableError, TableFormatter, TableSpan};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    CodeActionRequest, Formatting, OnTypeFormatting, RangeFormatting, Request as RequestTrait,
+};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, DocumentFormattingParams,
+    DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+    OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit,
+};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Runs the server on stdin and stdout until the client shuts it down.
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    Server::default().serve(&connection)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Describes what the server supports.
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+            first_trigger_character: "|".to_string(),
+            more_trigger_character: Some(vec!["\n".to_string()]),
+        }),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// An open document.
+struct Document {
+    text: String,
+    language_id: String,
+}
+
+/// The state of the server: open documents and the configuration that applies to them.
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+    configs: ConfigCache,
+}
+
+impl Server {
+    /// Handles messages until the client asks the server to shut down.
+    fn serve(&mut self, connection: &Connection) -> Result<(), Box<dyn Error>> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(published) = self.handle_notification(notification) {
+                        connection.sender.send(Message::Notification(published))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Response {
+        match request.method.as_str() {
+            Formatting::METHOD => dispatch::<Formatting>(request, |params| self.format(&params)),
+            RangeFormatting::METHOD => {
+                dispatch::<RangeFormatting>(request, |params| self.format_range(&params))
+            }
+            OnTypeFormatting::METHOD => {
+                dispatch::<OnTypeFormatting>(request, |params| self.format_on_type(&params))
+            }
+            CodeActionRequest::METHOD => {
+                dispatch::<CodeActionRequest>(request, |params| self.code_actions(&params))
+            }
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported method {}", request.method),
+            ),
+        }
+    }
+
+    /// Tracks open documents, returning their diagnostics when they change.
+    fn handle_notification(&mut self, notification: Notification) -> Option<Notification> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                let document = Document {
+                    text: params.text_document.text,
+                    language_id: params.text_document.language_id,
+                };
+                self.documents
+                    .insert(params.text_document.uri.clone(), document);
+                params.text_document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: lsp_types::DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                // With full sync, the last change holds the whole document
+                let text = params.content_changes.into_iter().last()?.text;
+                self.documents.get_mut(&params.text_document.uri)?.text = text;
+                params.text_document.uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                self.documents.remove(&params.text_document.uri);
+                // Clear the diagnostics of the closed document
+                return Some(publish(params.text_document.uri, Vec::new()));
+            }
+            _ => return None,
+        };
+
+        let diagnostics = self.diagnostics(&uri);
+        Some(publish(uri, diagnostics))
+    }
+
+    fn format(&mut self, params: &DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        self.table_edits(&params.text_document.uri, |_, _| true)
+    }
+
+    fn format_range(&mut self, params: &DocumentRangeFormattingParams) -> Option<Vec<TextEdit>> {
+        let (first, last) = line_bounds(params.range);
+        self.table_edits(&params.text_document.uri, |start, end| {
+            start <= last && first <= end
+        })
+    }
+
+    /// Formats the table being typed in, after a `|` or a new line.
+    fn format_on_type(&mut self, params: &DocumentOnTypeFormattingParams) -> Option<Vec<TextEdit>> {
+        let line = params.text_document_position.position.line as usize;
+        // After Enter the cursor is on the line below the row just finished
+        let first = if params.ch == "\n" {
+            line.saturating_sub(1)
+        } else {
+            line
+        };
+        self.table_edits(
+            &params.text_document_position.text_document.uri,
+            |start, end| start <= line && first <= end,
+        )
+    }
+
+    fn code_actions(&mut self, params: &CodeActionParams) -> Option<Vec<CodeActionOrCommand>> {
+        let uri = &params.text_document.uri;
+        let (first, last) = line_bounds(params.range);
+        let (mut formatter, dialect) = self.formatter(uri);
+        let text = &self.documents.get(uri)?.text;
+        let lines: Vec<&str> = text.lines().collect();
+        let overlaps = |start: usize, end: usize| start <= last && first < end;
+        let mut actions = Vec::new();
+
+        let (changes, diagnostics) = formatter.check_document_with_diagnostics_as(text, dialect);
+        for span in changes {
+            if overlaps(span.start_line, span.end_line) {
+                let edit = to_text_edit(span.edit(text));
+                actions.push(code_action("Format table", uri, edit));
+            }
+        }
+
+        if dialect == Dialect::Markdown {
+            for diagnostic in diagnostics {
+                if !overlaps(diagnostic.start_line, diagnostic.end_line) {
+                    continue;
+                }
+                if let Some(formatted) = with_separator_row(&mut formatter, &lines, &diagnostic) {
+                    let span = TableSpan {
+                        start_line: diagnostic.start_line,
+                        end_line: diagnostic.end_line,
+                        formatted,
+                    };
+                    let mut action = code_action(
+                        "Add missing separator row",
+                        uri,
+                        to_text_edit(span.edit(text)),
+                    );
+                    if let CodeActionOrCommand::CodeAction(action) = &mut action {
+                        action.diagnostics = Some(vec![to_diagnostic(&diagnostic)]);
+                        action.is_preferred = Some(true);
+                    }
+                    actions.push(action);
+                }
+            }
+        }
+
+        Some(actions)
+    }
+
+    /// Returns the edits formatting the changed tables whose first and last
+    /// lines are accepted by `filter`.
+    fn table_edits(
+        &mut self,
+        uri: &Url,
+        filter: impl Fn(usize, usize) -> bool,
+    ) -> Option<Vec<TextEdit>> {
+        let (mut formatter, dialect) = self.formatter(uri);
+        let text = &self.documents.get(uri)?.text;
+
+        let edits = formatter
+            .format_document_edits_as(text, dialect)
+            .into_iter()
+            .filter(|edit| {
+                // An edit ending at the start of a line does not include that line
+                let last = if edit.end.utf8_column == 0 {
+                    edit.end.line.saturating_sub(1)
+                } else {
+                    edit.end.line
+                };
+                filter(edit.start.line, last)
+            })
+            .map(to_text_edit)
+            .collect();
+        Some(edits)
+    }
+
+    /// Describes the tables of a document that cannot be formatted.
+    fn diagnostics(&mut self, uri: &Url) -> Vec<Diagnostic> {
+        let (mut formatter, dialect) = self.formatter(uri);
+        let Some(document) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        formatter
+            .diagnose_document_as(&document.text, dialect)
+            .iter()
+            .map(to_diagnostic)
+            .collect()
+    }
+
+    /// Creates a formatter and picks the dialect for a document from its
+    /// configuration, its language and its file extension.
+    fn formatter(&mut self, uri: &Url) -> (TableFormatter, Dialect) {
+        let path = uri.to_file_path().ok();
+        let settings = path
+            .as_deref()
+            .and_then(|path| match self.configs.for_path(path) {
+                Ok(config) => Some(config.format_settings(path)),
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        let language = self
+            .documents
+            .get(uri)
+            .and_then(|document| language_dialect(&document.language_id));
+        let dialect = match (settings.dialect, language) {
+            (None, Some(dialect)) => dialect,
+            _ => document_dialect(&settings, path.as_deref()),
+        };
+
+        (formatter(&settings), dialect)
+    }
+}
+
+/// Parses the parameters of a request and responds with the handler's result.
+fn dispatch<R>(request: Request, handler: impl FnOnce(R::Params) -> R::Result) -> Response
+where
+    R: RequestTrait,
+{
+    let id = request.id.clone();
+    match request.extract::<R::Params>(R::METHOD) {
+        Ok((id, params)) => Response::new_ok(id, handler(params)),
+        Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, format!("{e:?}")),
+    }
+}
+
+/// Creates a `textDocument/publishDiagnostics` notification.
+fn publish(uri: Url, diagnostics: Vec<Diagnostic>) -> Notification {
+    let params = PublishDiagnosticsParams {
+        uri,
+        diagnostics,
+        version: None,
+    };
+    Notification::new(PublishDiagnostics::METHOD.to_string(), params)
+}
+
+/// Maps an LSP language identifier to a dialect.
+fn language_dialect(language_id: &str) -> Option<Dialect> {
+    match language_id {
+        "markdown" => Some(Dialect::Markdown),
+        "restructuredtext" | "rst" => Some(Dialect::Rst),
+        "org" => Some(Dialect::Org),
+        _ => None,
+    }
+}
+
+/// Returns the first and last lines touched by a range. A range ending at
+/// the start of a line does not include that line.
+fn line_bounds(range: Range) -> (usize, usize) {
+    let first = range.start.line as usize;
+    let last = if range.end.character == 0 && range.end.line > range.start.line {
+        range.end.line as usize - 1
+    } else {
+        range.end.line as usize
+    };
+    (first, last)
+}
+
+/// Converts a library edit to an LSP edit.
+fn to_text_edit(edit: ftb::TextEdit) -> TextEdit {
+    TextEdit {
+        range: Range::new(to_position(edit.start), to_position(edit.end)),
+        new_text: edit.new_text,
+    }
+}
+
+/// Turns a table that could not be formatted into an LSP diagnostic.
+fn to_diagnostic(diagnostic: &TableDiagnostic) -> Diagnostic {
+    let severity = match diagnostic.severity {
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Error => DiagnosticSeverity::ERROR,
+    };
+
+    Diagnostic {
+        range: Range::new(
+            to_position(diagnostic.span.start),
+            to_position(diagnostic.span.end),
+        ),
+        severity: Some(severity),
+        source: Some("ftb".to_string()),
+        message: diagnostic.error.to_string(),
+        ..Diagnostic::default()
+    }
+}
+
+/// Converts a library position to an LSP position, whose column counts UTF-16 code units.
+fn to_position(position: ftb::TextPosition) -> Position {
+    Position::new(
+        line_number(position.line),
+        u32::try_from(position.utf16_column).unwrap_or(u32::MAX),
+    )
+}
+
+/// Creates a quick fix applying one edit.
+fn code_action(title: &str, uri: &Url, edit: TextEdit) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+            ..WorkspaceEdit::default()
+        }),
+        ..CodeAction::default()
+    })
+}
+
+/// Formats a Markdown table after inserting a separator row below its
+/// first row, or returns `None` if that does not make it a valid table.
+///
+/// Only tables with no separator row at all qualify. A second row that was
+/// meant as a separator, such as `|-x-|`, needs fixing, not another row.
+fn with_separator_row(
+    formatter: &mut TableFormatter,
+    lines: &[&str],
+    diagnostic: &TableDiagnostic,
+) -> Option<String> {
+    let block = &lines[diagnostic.start_line..diagnostic.end_line];
+    let missing = match diagnostic.error {
+        TableError::MissingSeparator => true,
+        TableError::InvalidStructure(_) => !block.get(1).is_some_and(|row| is_separator_like(row)),
+        _ => false,
+    };
+    if !missing {
+        return None;
+    }
+    let header = block.first()?;
+    let separator = format!("|{}", "---|".repeat(count_cells(header)));
+
+    let table = std::iter::once(*header)
+        .chain(std::iter::once(separator.as_str()))
+        .chain(block[1..].iter().copied())
+        .collect::<Vec<_>>()
+        .join("\n");
+    formatter.format_table(&table).ok()
+}
+
+/// Returns whether a row looks like an attempt at a separator row, that is
+/// whether one of its cells starts with a dash or colon.
+fn is_separator_like(row: &str) -> bool {
+    row.split('|')
+        .map(str::trim)
+        .any(|cell| cell.starts_with('-') || cell.starts_with(':'))
+}
+
+/// Counts the cells of a Markdown table row, ignoring escaped pipes.
+fn count_cells(row: &str) -> usize {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = match row.strip_suffix('|') {
+        Some(inner) if !inner.ends_with('\\') => inner,
+        _ => row,
+    };
+
+    let mut cells = 1;
+    let mut escaped = false;
+    for c in row.chars() {
+        if c == '|' && !escaped {
+            cells += 1;
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells
+}
+
+/// Converts a line index to an LSP line number.
+fn line_number(line: usize) -> u32 {
+    u32::try_from(line).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+    use serde_json::{json, Value};
+    use std::thread;
+
+    /// Runs a server on an in-memory connection, sends it `messages`, shuts it
+    /// down and returns everything it sent back.
+    fn exchange(messages: Vec<Message>) -> Vec<Message> {
+        let (server, client) = Connection::memory();
+        let handle = thread::spawn(move || Server::default().serve(&server).is_ok());
+
+        for message in messages {
+            client.sender.send(message).unwrap();
+        }
+        let shutdown = Request::new(RequestId::from(99), "shutdown".to_string(), ());
+        client.sender.send(shutdown.into()).unwrap();
+        client
+            .sender
+            .send(Notification::new("exit".to_string(), ()).into())
+            .unwrap();
+        assert!(handle.join().unwrap());
+
+        client.receiver.try_iter().collect()
+    }
+
+    fn open(text: &str) -> Message {
+        Notification::new(
+            DidOpenTextDocument::METHOD.to_string(),
+            json!({"textDocument": {
+                "uri": "untitled:doc.md", "languageId": "markdown", "version": 1, "text": text
+            }}),
+        )
+        .into()
+    }
+
+    fn request(id: i32, method: &str, params: Value) -> Message {
+        Request::new(RequestId::from(id), method.to_string(), params).into()
+    }
+
+    /// Returns the result of the response to request `id`.
+    fn result(messages: &[Message], id: i32) -> Value {
+        messages
+            .iter()
+            .find_map(|message| match message {
+                Message::Response(response) if response.id == RequestId::from(id) => {
+                    response.result.clone()
+                }
+                _ => None,
+            })
+            .expect("Should respond")
+    }
+
+    #[test]
+    fn test_formatting_returns_one_edit_per_table() {
+        let text = "| a | b |\n|-|-|\n\ntext\n\n| c |\n|---|\n| long |";
+        let messages = exchange(vec![
+            open(text),
+            request(
+                1,
+                Formatting::METHOD,
+                json!({"textDocument": {"uri": "untitled:doc.md"}, "options": {"tabSize": 4, "insertSpaces": true}}),
+            ),
+        ]);
+
+        assert_eq!(
+            result(&messages, 1),
+            json!([
+                {
+                    "range": {"start": {"line": 0, "character": 0}, "end": {"line": 2, "character": 0}},
+                    "newText": "| a | b |\n|---|---|\n"
+                },
+                {
+                    "range": {"start": {"line": 5, "character": 0}, "end": {"line": 7, "character": 8}},
+                    "newText": "| c    |\n|------|\n| long |"
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_and_separator_code_action() {
+        let text = "# Title\n\n| a | b |\n| 1 | 2 |\n";
+        let messages = exchange(vec![
+            open(text),
+            request(
+                1,
+                CodeActionRequest::METHOD,
+                json!({
+                    "textDocument": {"uri": "untitled:doc.md"},
+                    "range": {"start": {"line": 2, "character": 3}, "end": {"line": 2, "character": 3}},
+                    "context": {"diagnostics": []}
+                }),
+            ),
+        ]);
+
+        let Message::Notification(published) = &messages[0] else {
+            panic!("Should publish diagnostics first");
+        };
+        let diagnostics = &published.params["diagnostics"];
+        assert_eq!(diagnostics.as_array().map(Vec::len), Some(1));
+        // The second row is not a separator, and its first cell is the culprit
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({"start": {"line": 3, "character": 2}, "end": {"line": 3, "character": 3}})
+        );
+
+        let actions = result(&messages, 1);
+        assert_eq!(actions[0]["title"], "Add missing separator row");
+        assert_eq!(
+            actions[0]["edit"]["changes"]["untitled:doc.md"][0]["newText"],
+            "| a | b |\n|---|---|\n| 1 | 2 |\n"
+        );
+    }
+
+    #[test]
+    fn test_no_separator_action_for_malformed_separator() {
+        let text = "| a | b |\n|-x-|---|\n| 1 | 2 |\n";
+        let messages = exchange(vec![
+            open(text),
+            request(
+                1,
+                CodeActionRequest::METHOD,
+                json!({
+                    "textDocument": {"uri": "untitled:doc.md"},
+                    "range": {"start": {"line": 1, "character": 1}, "end": {"line": 1, "character": 1}},
+                    "context": {"diagnostics": []}
+                }),
+            ),
+        ]);
+
+        assert_eq!(result(&messages, 1), json!([]));
+    }
+
+    #[test]
+    fn test_on_type_formatting_only_touches_current_table() {
+        let text = "| a |\n|-|\n\n| b |\n|-|\n| cc |\n";
+        let messages = exchange(vec![
+            open(text),
+            request(
+                1,
+                OnTypeFormatting::METHOD,
+                json!({
+                    "textDocument": {"uri": "untitled:doc.md"},
+                    "position": {"line": 6, "character": 0},
+                    "ch": "\n",
+                    "options": {"tabSize": 4, "insertSpaces": true}
+                }),
+            ),
+        ]);
+
+        let edits = result(&messages, 1);
+        assert_eq!(edits.as_array().map(Vec::len), Some(1));
+        assert_eq!(edits[0]["range"]["start"]["line"], 3);
+    }
+
+    #[test]
+    fn test_count_cells() {
+        assert_eq!(count_cells("| a | b |"), 2);
+        assert_eq!(count_cells("a | b \\| c"), 2);
+        assert_eq!(count_cells("| a \\|"), 1);
+    }
+}
@@ -1,47 +1,1016 @@
-use clap::Parser;
-use ftb::TableFormatter;
+use clap::{Parser, Subcommand, ValueEnum};
+use config::{Config, ConfigCache, FileSettings, FormatSettings};
+use ftb::box_drawing::{BoxOptions, BoxStyle};
+use ftb::columns::ColumnsOptions;
+use ftb::csv::CsvOptions;
+use ftb::html::HtmlOptions;
+use ftb::json::{JsonImportOptions, JsonOptions};
+use ftb::latex::LatexOptions;
+use ftb::{
+    Dialect, FormattedDocument, Position, Severity, Table, TableDiagnostic, TableEdit,
+    TableFormatter, TableSpan, TextPosition, WidthPolicy,
+};
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::{self, ErrorKind, Read};
+use std::io::{self, ErrorKind, IsTerminal, Read};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::process;
 
+mod config;
+mod diff;
+mod files;
+mod lsp;
+
+/// Exit status when `--check` finds tables that are not formatted.
+///
+/// Errors exit with 1 and usage errors with 2, so CI can tell them apart.
+const EXIT_UNFORMATTED: i32 = 3;
+
 /// Format and align Markdown tables
 ///
-/// Reads a Markdown table from stdin or a file and outputs a properly aligned version.
+/// Reads a Markdown table from stdin or files and outputs a properly aligned version.
 /// Perfect for use with pipes: pbpaste | ftb
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    /// Input file (reads from stdin if not provided)
-    input: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Files, directories or glob patterns to format (reads from stdin if none are given)
+    #[arg(value_name = "PATH")]
+    inputs: Vec<PathBuf>,
+
+    /// Rewrite files in place instead of printing them
+    #[arg(short, long, conflicts_with_all = ["check", "from", "to"])]
+    write: bool,
+
+    /// File extensions to format when walking directories [default: md,markdown]
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    extensions: Option<Vec<String>>,
+
+    /// Convert the input from another format into a Markdown table
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    from: Option<InputFormat>,
+
+    /// Markup language of the document (guessed from the file extension by default)
+    #[arg(long, value_enum)]
+    dialect: Option<DialectArg>,
+
+    /// How to measure characters such as ° and … whose width depends on the font [default: standard]
+    #[arg(long, value_enum)]
+    width: Option<WidthArg>,
+
+    /// Report tables that are not formatted instead of printing the document
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    check: bool,
+
+    /// Report tables that could not be formatted, with their file and lines,
+    /// and exit with status 1
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    strict: bool,
+
+    /// How --strict reports tables that could not be formatted
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value = "human",
+        requires = "strict"
+    )]
+    error_format: ErrorFormat,
+
+    /// Format only the tables overlapping lines START to END (1-based, inclusive)
+    #[arg(
+        long,
+        value_name = "START:END",
+        value_parser = parse_line_range,
+        conflicts_with_all = ["check", "cursor", "from", "to"]
+    )]
+    range: Option<(usize, usize)>,
+
+    /// Format only the table at LINE:COL (1-based, COL in characters) and
+    /// print the cursor's new LINE:COL to stderr
+    #[arg(
+        long,
+        value_name = "LINE:COL",
+        value_parser = parse_cursor,
+        conflicts_with_all = ["check", "diff", "from", "to"]
+    )]
+    cursor: Option<Position>,
+
+    /// Print a unified diff of the changes formatting would make
+    #[arg(long, conflicts_with_all = ["write", "from", "to"])]
+    diff: bool,
+
+    /// When to color diff output
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    color: ColorChoice,
+
+    /// Convert a Markdown table into another format instead of formatting the document
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    to: Option<OutputFormat>,
+
+    /// Which table of the input to convert (1 is the first)
+    #[arg(long, value_name = "N", default_value = "1")]
+    table: NonZeroUsize,
+
+    /// Convert JSON values to numbers, booleans and null based on inferred column types
+    #[arg(long, overrides_with = "no_coerce_types")]
+    coerce_types: bool,
+
+    /// Keep all JSON values as strings, even if the configuration coerces them
+    #[arg(long)]
+    no_coerce_types: bool,
+
+    /// Use inline text-align styles instead of align attributes in HTML output
+    #[arg(long, overrides_with = "no_html_inline_styles")]
+    html_inline_styles: bool,
+
+    /// Use align attributes in HTML output, even if the configuration asks for styles
+    #[arg(long)]
+    no_html_inline_styles: bool,
+
+    /// Use booktabs rules (\toprule, \midrule, \bottomrule) in LaTeX output
+    #[arg(long, overrides_with = "no_latex_booktabs")]
+    latex_booktabs: bool,
+
+    /// Use plain \hline rules in LaTeX output, even if the configuration asks for booktabs
+    #[arg(long)]
+    no_latex_booktabs: bool,
+
+    /// Border style for box-drawing output [default: single]
+    #[arg(long, value_enum)]
+    box_style: Option<BoxStyleArg>,
+}
+
+// Variant names become the `from-*` subcommand names
+#[allow(clippy::enum_variant_names)]
+#[derive(Subcommand)]
+enum Command {
+    /// Convert CSV or TSV data into a formatted Markdown table
+    FromCsv {
+        /// Input file (reads from stdin if not provided)
+        input: Option<PathBuf>,
+
+        /// Field delimiter (a single character, or "tab")
+        #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: char,
+
+        /// Read tab-separated values (same as --delimiter tab)
+        #[arg(long, conflicts_with = "delimiter")]
+        tsv: bool,
+
+        /// Treat the first record as data and generate column names
+        #[arg(long)]
+        no_header: bool,
+    },
+
+    /// Convert a JSON array of objects (or NDJSON) into a formatted Markdown table
+    FromJson {
+        /// Input file (reads from stdin if not provided)
+        input: Option<PathBuf>,
+
+        /// Comma-separated list of columns to include, in order (nested keys use dots)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+    },
+
+    /// Convert whitespace-aligned text (e.g. `kubectl get pods`) into a formatted Markdown table
+    FromColumns {
+        /// Input file (reads from stdin if not provided)
+        input: Option<PathBuf>,
+
+        /// Treat the first line as data and generate column names
+        #[arg(long)]
+        no_header: bool,
+    },
+
+    /// Convert a worksheet of an XLSX or ODS spreadsheet into a formatted Markdown table
+    #[cfg(feature = "sheet")]
+    FromSheet {
+        /// Spreadsheet file (.xlsx, .xlsm, .xlsb, .xls or .ods)
+        input: PathBuf,
+
+        /// Worksheet to read (defaults to the first sheet)
+        #[arg(long)]
+        sheet: Option<String>,
+
+        /// Cell range to read, e.g. A1:F20 (defaults to the used area)
+        #[arg(long)]
+        range: Option<String>,
+    },
+
+    /// Edit the Markdown table at a cursor and print the new document and cursor as JSON
+    Edit {
+        /// The edit to make
+        operation: EditArg,
+
+        /// Cursor position as a byte offset into the document
+        #[arg(long)]
+        offset: usize,
+
+        /// Input file (reads from stdin if not provided)
+        input: Option<PathBuf>,
+    },
+
+    /// Run a Language Server Protocol server on stdio for editors
+    Lsp,
+
+    /// Inspect the configuration read from .ftb.toml or pyproject.toml
+    Config {
+        /// Print the effective settings, after configuration files and flags
+        #[arg(long, required = true)]
+        show: bool,
+
+        /// File or directory to show the settings for (defaults to the current directory)
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InputFormat {
+    /// Comma-separated values with a header record
+    Csv,
+    /// Tab-separated values with a header record
+    Tsv,
+    /// A JSON array of objects, or one object per line
+    Json,
+    /// HTML <table> markup
+    Html,
+    /// An Org-mode table
+    Org,
+    /// A Pandoc grid table, whose cells may span several lines
+    Grid,
+    /// Jira or Confluence wiki markup (||header|| and |cell|)
+    Jira,
+    /// A MediaWiki {| ... |} table
+    Mediawiki,
+    /// Query results printed by psql, the MySQL client or sqlite
+    Sql,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum DialectArg {
+    /// Markdown pipe tables
+    Markdown,
+    /// reStructuredText grid and simple tables
+    Rst,
+    /// Org-mode tables
+    Org,
+}
+
+impl From<DialectArg> for Dialect {
+    fn from(dialect: DialectArg) -> Self {
+        match dialect {
+            DialectArg::Markdown => Dialect::Markdown,
+            DialectArg::Rst => Dialect::Rst,
+            DialectArg::Org => Dialect::Org,
+        }
+    }
+}
+
+impl From<Dialect> for DialectArg {
+    fn from(dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::Markdown => DialectArg::Markdown,
+            Dialect::Rst => DialectArg::Rst,
+            Dialect::Org => DialectArg::Org,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum WidthArg {
+    /// Characters such as ° and … take one column, as in most Western fonts
+    #[default]
+    Standard,
+    /// Characters such as ° and … take two columns, as in CJK fonts
+    Cjk,
+}
+
+impl From<WidthArg> for WidthPolicy {
+    fn from(width: WidthArg) -> Self {
+        match width {
+            WidthArg::Standard => WidthPolicy::Standard,
+            WidthArg::Cjk => WidthPolicy::Cjk,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EditArg {
+    /// Move to the next cell, adding a row after the last one
+    NextCell,
+    /// Move to the previous cell
+    PreviousCell,
+    /// Insert an empty row below the cursor
+    InsertRowBelow,
+    /// Delete the row under the cursor
+    DeleteRow,
+    /// Insert an empty column left of the cursor
+    InsertColumnLeft,
+    /// Insert an empty column right of the cursor
+    InsertColumnRight,
+    /// Delete the column under the cursor
+    DeleteColumn,
+    /// Swap the column under the cursor with the one on its left
+    MoveColumnLeft,
+    /// Swap the column under the cursor with the one on its right
+    MoveColumnRight,
+    /// Swap the row under the cursor with the one above
+    MoveRowUp,
+    /// Swap the row under the cursor with the one below
+    MoveRowDown,
+}
+
+impl From<EditArg> for TableEdit {
+    fn from(edit: EditArg) -> Self {
+        match edit {
+            EditArg::NextCell => TableEdit::NextCell,
+            EditArg::PreviousCell => TableEdit::PreviousCell,
+            EditArg::InsertRowBelow => TableEdit::InsertRowBelow,
+            EditArg::DeleteRow => TableEdit::DeleteRow,
+            EditArg::InsertColumnLeft => TableEdit::InsertColumnLeft,
+            EditArg::InsertColumnRight => TableEdit::InsertColumnRight,
+            EditArg::DeleteColumn => TableEdit::DeleteColumn,
+            EditArg::MoveColumnLeft => TableEdit::MoveColumnLeft,
+            EditArg::MoveColumnRight => TableEdit::MoveColumnRight,
+            EditArg::MoveRowUp => TableEdit::MoveRowUp,
+            EditArg::MoveRowDown => TableEdit::MoveRowDown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum BoxStyleArg {
+    /// Light lines: ┌─┬─┐
+    #[default]
+    Single,
+    /// Double lines: ╔═╦═╗
+    Double,
+    /// Light lines with rounded corners: ╭─┬─╮
+    Rounded,
+    /// Plain ASCII: +-+-+
+    Ascii,
+    /// Light lines with a heavy rule under the header
+    Heavy,
+}
+
+impl From<BoxStyleArg> for BoxStyle {
+    fn from(style: BoxStyleArg) -> Self {
+        match style {
+            BoxStyleArg::Single => BoxStyle::Single,
+            BoxStyleArg::Double => BoxStyle::Double,
+            BoxStyleArg::Rounded => BoxStyle::Rounded,
+            BoxStyleArg::Ascii => BoxStyle::Ascii,
+            BoxStyleArg::Heavy => BoxStyle::Heavy,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    /// Color when printing to a terminal and NO_COLOR is not set
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Decides whether to color output written to stdout.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ErrorFormat {
+    /// The offending lines with carets under the problem
+    Human,
+    /// One FILE:START-END: MESSAGE line per table
+    Short,
+    /// One JSON object per line, for tools
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Comma-separated values
+    Csv,
+    /// Tab-separated values
+    Tsv,
+    /// A JSON array of objects keyed by header
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// An HTML table with escaped content
+    Html,
+    /// An AsciiDoc |=== table
+    Asciidoc,
+    /// A reStructuredText grid table
+    RstGrid,
+    /// A reStructuredText list-table directive
+    RstList,
+    /// An Org-mode table
+    Org,
+    /// A Pandoc grid table with multi-line cells
+    Grid,
+    /// A LaTeX tabular environment
+    Latex,
+    /// A Typst #table call
+    Typst,
+    /// Jira or Confluence wiki markup
+    Jira,
+    /// A MediaWiki wikitable
+    Mediawiki,
+    /// Box-drawing borders for reading in a terminal
+    Box,
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    if let Err(e) = run(cli) {
-        eprintln!("Error: {e}");
-        process::exit(1);
+    match run(cli) {
+        Ok(0) => {}
+        Ok(status) => process::exit(status),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(1);
+        }
     }
 }
 
-fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    const MAX_INPUT_SIZE: u64 = 10 * 1024 * 1024; // 10MB
+/// Runs the CLI and returns the exit status.
+fn run(mut cli: Cli) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut configs = ConfigCache::default();
+
+    if let Some(command) = cli.command.take() {
+        run_command(command, &cli, &mut configs)?;
+        return Ok(0);
+    }
+
+    if cli.inputs.is_empty() {
+        if cli.write {
+            return Err("--write needs files to rewrite".into());
+        }
+        let input = read_input(None)?;
+        let settings = document_settings(&cli, &mut configs, None)?;
+        return run_document(&input, None, &cli, &settings);
+    }
+
+    let files = files::collect_files(&cli.inputs, |file| {
+        Ok(configs
+            .for_path(file)?
+            .selects(file, cli.extensions.as_deref()))
+    })?;
+    if (cli.range.is_some() || cli.cursor.is_some()) && files.len() > 1 {
+        return Err("--range and --cursor apply to a single document".into());
+    }
+    if !cli.write && !cli.check && !cli.diff {
+        return match (files.as_slice(), cli.inputs.as_slice()) {
+            ([file], _) if cli.inputs == files => {
+                let settings = document_settings(&cli, &mut configs, Some(file))?;
+                run_document(&read_input(Some(file))?, Some(file), &cli, &settings)
+            }
+            // Name the problem even when a directory holds a single file
+            (_, [input]) if input.is_dir() => Err(format!(
+                "Formatting the files in directory {} needs --write, --check or --diff",
+                input.display()
+            )
+            .into()),
+            (_, [input]) => Err(format!(
+                "Formatting the files matching {} needs --write, --check or --diff",
+                input.display()
+            )
+            .into()),
+            _ => Err("Several input files need --write, --check or --diff".into()),
+        };
+    }
+
+    // Keep going after a failure so one bad file does not hide the others
+    let mut status = 0;
+    let mut failed = false;
+    for file in &files {
+        let result = document_settings(&cli, &mut configs, Some(file))
+            .map_err(Into::into)
+            .and_then(|settings| {
+                if cli.write {
+                    write_file(file, &cli, &settings)
+                } else {
+                    read_input(Some(file))
+                        .and_then(|input| run_document(&input, Some(file), &cli, &settings))
+                }
+            });
+        match result {
+            Ok(file_status) => status = status.max(file_status),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                failed = true;
+            }
+        }
+    }
+
+    Ok(if failed { 1 } else { status })
+}
 
-    let input = if let Some(path) = cli.input {
-        read_file(&path, MAX_INPUT_SIZE)?
+/// Formats, checks or converts one document and returns the exit status.
+fn run_document(
+    input: &str,
+    path: Option<&Path>,
+    cli: &Cli,
+    settings: &FormatSettings,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let dialect = document_dialect(settings, path);
+
+    if cli.from.is_some() || cli.to.is_some() {
+        let table = read_table(input, cli.from, cli.table)?;
+        print!("{}", render_table(&table, cli.to, settings)?);
+        return Ok(0);
+    }
+
+    if cli.check && !cli.diff {
+        let (changes, diagnostics) =
+            formatter(settings).check_document_with_diagnostics_as(input, dialect);
+        let skipped = cli.strict && report_skipped(input, path, cli, &diagnostics);
+        let status = check(&changes, path);
+        return Ok(if skipped { status.max(1) } else { status });
+    }
+
+    let (formatted, cursor) = format_document(input, dialect, cli, settings);
+    let skipped = cli.strict && report_skipped(input, path, cli, &formatted.diagnostics);
+
+    let status = if cli.diff {
+        print!(
+            "{}",
+            diff::unified_diff(
+                input,
+                &formatted.text,
+                &display_name(path),
+                cli.color.enabled()
+            )
+        );
+        // Combined with --check, a non-empty diff fails like a --check report
+        let changed = formatted.text != input;
+        if cli.check && changed {
+            EXIT_UNFORMATTED
+        } else {
+            0
+        }
+    } else {
+        report_cursor(cursor);
+        print!("{}", formatted.text);
+        0
+    };
+
+    Ok(if skipped { status.max(1) } else { status })
+}
+
+/// Formats a file in place, leaving it untouched when nothing changes, and
+/// returns the exit status.
+fn write_file(
+    path: &Path,
+    cli: &Cli,
+    settings: &FormatSettings,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let input = read_input(Some(path))?;
+    let dialect = document_dialect(settings, Some(path));
+    let (formatted, cursor) = format_document(&input, dialect, cli, settings);
+    let skipped = cli.strict && report_skipped(&input, Some(path), cli, &formatted.diagnostics);
+
+    if formatted.text != input {
+        files::write_atomic(path, &formatted.text)
+            .map_err(|e| format!("Failed to write file: {}\nError: {e}", path.display()))?;
+    }
+    report_cursor(cursor);
+
+    Ok(i32::from(skipped))
+}
+
+/// Formats the tables of a document, or only those picked by `--range` or
+/// `--cursor`. Returns the cursor's new position when `--cursor` is given.
+///
+/// The diagnostics cover the whole document and come from the same scan as
+/// the text.
+fn format_document(
+    input: &str,
+    dialect: Dialect,
+    cli: &Cli,
+    settings: &FormatSettings,
+) -> (FormattedDocument, Option<Position>) {
+    let mut formatter = formatter(settings);
+    match (cli.range, cli.cursor) {
+        (_, Some(cursor)) => {
+            let (formatted, cursor) =
+                formatter.format_at_cursor_with_diagnostics_as(input, dialect, cursor);
+            (formatted, Some(cursor))
+        }
+        (Some((start, end)), None) => (
+            formatter.format_range_with_diagnostics_as(input, dialect, start..end),
+            None,
+        ),
+        (None, None) => (
+            formatter.format_document_with_diagnostics_as(input, dialect),
+            None,
+        ),
+    }
+}
+
+/// Prints the cursor position left by `--cursor` to stderr as 1-based LINE:COL.
+fn report_cursor(cursor: Option<Position>) {
+    if let Some(cursor) = cursor {
+        eprintln!("{}:{}", cursor.line + 1, cursor.column + 1);
+    }
+}
+
+/// Combines the command-line flags with the configuration for a document.
+///
+/// Flags take precedence over per-path overrides, which take precedence over
+/// the base configuration. Documents read from stdin use the configuration
+/// of the current directory.
+fn document_settings(
+    cli: &Cli,
+    configs: &mut ConfigCache,
+    path: Option<&Path>,
+) -> Result<FormatSettings, String> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => std::env::current_dir()
+            .map_err(|e| format!("Cannot access the current directory: {e}"))?,
+    };
+    let project = configs.for_path(&path)?;
+
+    Ok(flag_settings(cli).or(project.format_settings(&path)))
+}
+
+/// Collects the format settings given as command-line flags.
+fn flag_settings(cli: &Cli) -> FormatSettings {
+    FormatSettings {
+        dialect: cli.dialect,
+        width: cli.width,
+        box_style: cli.box_style,
+        html_inline_styles: switch(cli.html_inline_styles, cli.no_html_inline_styles),
+        latex_booktabs: switch(cli.latex_booktabs, cli.no_latex_booktabs),
+        coerce_types: switch(cli.coerce_types, cli.no_coerce_types),
+    }
+}
+
+/// Reads a pair of `--x`/`--no-x` flags, `None` meaning neither was given.
+fn switch(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    }
+}
+
+/// Creates a formatter for the given settings.
+fn formatter(settings: &FormatSettings) -> TableFormatter {
+    TableFormatter::new().with_width_policy(settings.width.unwrap_or_default().into())
+}
+
+/// Picks the dialect from the settings, then the file extension, then Markdown.
+fn document_dialect(settings: &FormatSettings, path: Option<&Path>) -> Dialect {
+    settings
+        .dialect
+        .map(Dialect::from)
+        .or_else(|| path.and_then(Dialect::from_path))
+        .unwrap_or_default()
+}
+
+/// Prints the settings that apply to a path, filling in defaults, as TOML.
+fn show_config(
+    cli: &Cli,
+    configs: &mut ConfigCache,
+    path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let project = configs.for_path(&path)?;
+    let settings = document_settings(cli, configs, Some(&path))?;
+
+    let effective = Config {
+        files: FileSettings {
+            extensions: Some(
+                cli.extensions
+                    .clone()
+                    .unwrap_or_else(|| project.extensions()),
+            ),
+            include: project.config.files.include.clone(),
+            exclude: project.config.files.exclude.clone(),
+        },
+        format: FormatSettings {
+            dialect: Some(document_dialect(&settings, Some(&path)).into()),
+            width: Some(settings.width.unwrap_or_default()),
+            box_style: Some(settings.box_style.unwrap_or_default()),
+            html_inline_styles: Some(settings.html_inline_styles.unwrap_or_default()),
+            latex_booktabs: Some(settings.latex_booktabs.unwrap_or_default()),
+            coerce_types: Some(settings.coerce_types.unwrap_or_default()),
+        },
+        overrides: Vec::new(),
+    };
+
+    match &project.source {
+        Some(source) => println!(
+            "# Settings for {} from {}",
+            path.display(),
+            source.display()
+        ),
+        None => println!(
+            "# Settings for {} (no configuration file found)",
+            path.display()
+        ),
+    }
+    print!("{}", toml::to_string(&effective)?);
+    Ok(())
+}
+
+/// Prints the line range of every table that formatting would change, the
+/// way `rustfmt --check` reports files, and returns the exit status.
+fn check(changes: &[TableSpan], path: Option<&Path>) -> i32 {
+    let name = display_name(path);
+
+    for change in changes {
+        println!(
+            "{name}:{}-{}: table is not formatted",
+            change.start_line + 1,
+            change.end_line
+        );
+    }
+
+    if changes.is_empty() {
+        0
     } else {
-        read_stdin(MAX_INPUT_SIZE)?
+        EXIT_UNFORMATTED
+    }
+}
+
+/// Prints each table that cannot be formatted to stderr as an error, in the
+/// format chosen with `--error-format`. Returns whether there were any.
+fn report_skipped(
+    input: &str,
+    path: Option<&Path>,
+    cli: &Cli,
+    diagnostics: &[TableDiagnostic],
+) -> bool {
+    let name = display_name(path);
+
+    for mut diagnostic in diagnostics.iter().cloned() {
+        diagnostic.severity = Severity::Error;
+        match cli.error_format {
+            ErrorFormat::Human => eprintln!("{}", diagnostic.render(input, &name)),
+            ErrorFormat::Short => eprintln!(
+                "{name}:{}-{}: {}",
+                diagnostic.start_line + 1,
+                diagnostic.end_line,
+                diagnostic.error
+            ),
+            ErrorFormat::Json => eprintln!("{}", diagnostic_json(input, &name, &diagnostic)),
+        }
+    }
+
+    !diagnostics.is_empty()
+}
+
+/// Describes a diagnostic as JSON, with 1-based lines and columns counted in
+/// characters, and 0-based byte offsets.
+fn diagnostic_json(input: &str, name: &str, diagnostic: &TableDiagnostic) -> serde_json::Value {
+    let position = |offset: usize, position: TextPosition| {
+        let line_start = offset - position.utf8_column;
+        serde_json::json!({
+            "line": position.line + 1,
+            "column": input[line_start..offset].chars().count() + 1,
+            "byte": offset,
+        })
+    };
+    let span = &diagnostic.span;
+
+    serde_json::json!({
+        "file": name,
+        "severity": diagnostic.severity.to_string(),
+        "message": diagnostic.error.to_string(),
+        "start_line": diagnostic.start_line + 1,
+        "end_line": diagnostic.end_line,
+        "span": {
+            "start": position(span.range.start, span.start),
+            "end": position(span.range.end, span.end),
+        },
+    })
+}
+
+/// Names a document in reports, using `<stdin>` when it has no path.
+fn display_name(path: Option<&Path>) -> String {
+    path.map_or_else(|| "<stdin>".to_string(), |path| path.display().to_string())
+}
+
+/// Reads the table to convert, either from another format or from a Markdown document.
+fn read_table(
+    input: &str,
+    from: Option<InputFormat>,
+    index: NonZeroUsize,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    let table = match from {
+        Some(InputFormat::Csv) => ftb::csv::parse_table(input, &CsvOptions::csv())?,
+        Some(InputFormat::Tsv) => ftb::csv::parse_table(input, &CsvOptions::tsv())?,
+        Some(InputFormat::Json) => ftb::json::parse_table(input, &JsonImportOptions::default())?,
+        Some(InputFormat::Html) => select_table(ftb::html::parse_tables(input)?, index)?,
+        Some(InputFormat::Org) => ftb::org::parse_table(input)?,
+        Some(InputFormat::Grid) => select_table(ftb::grid::parse_tables(input), index)?,
+        Some(InputFormat::Jira) => select_table(ftb::jira::parse_tables(input)?, index)?,
+        Some(InputFormat::Mediawiki) => select_table(ftb::mediawiki::parse_tables(input)?, index)?,
+        Some(InputFormat::Sql) => ftb::sql::parse_table(input)?,
+        None => select_table(TableFormatter::new().parse_document(input), index)?,
+    };
+
+    Ok(table)
+}
+
+/// Picks the table at a 1-based position.
+fn select_table(tables: Vec<Table>, index: NonZeroUsize) -> Result<Table, String> {
+    let count = tables.len();
+    tables
+        .into_iter()
+        .nth(index.get() - 1)
+        .ok_or_else(|| format!("Table {index} not found: the input contains {count} table(s)"))
+}
+
+/// Renders a table in the requested output format, Markdown by default.
+fn render_table(
+    table: &Table,
+    to: Option<OutputFormat>,
+    settings: &FormatSettings,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let json_options = JsonOptions {
+        coerce_types: settings.coerce_types.unwrap_or_default(),
+    };
+
+    let output = match to {
+        None => table.to_markdown_as(settings.width.unwrap_or_default().into())?,
+        Some(OutputFormat::Csv) => ftb::csv::write_table(table, ','),
+        Some(OutputFormat::Tsv) => ftb::csv::write_table(table, '\t'),
+        Some(OutputFormat::Json) => ftb::json::write_json(table, &json_options),
+        Some(OutputFormat::Ndjson) => ftb::json::write_ndjson(table, &json_options),
+        Some(OutputFormat::Html) => ftb::html::write_table(
+            table,
+            &HtmlOptions {
+                inline_styles: settings.html_inline_styles.unwrap_or_default(),
+            },
+        ),
+        Some(OutputFormat::Asciidoc) => ftb::asciidoc::write_table(table),
+        Some(OutputFormat::RstGrid) => ftb::rst::write_grid_table(table),
+        Some(OutputFormat::RstList) => ftb::rst::write_list_table(table),
+        Some(OutputFormat::Org) => ftb::org::write_table(table),
+        Some(OutputFormat::Grid) => ftb::grid::write_table(table),
+        Some(OutputFormat::Latex) => ftb::latex::write_table(
+            table,
+            &LatexOptions {
+                booktabs: settings.latex_booktabs.unwrap_or_default(),
+            },
+        ),
+        Some(OutputFormat::Typst) => ftb::typst::write_table(table),
+        Some(OutputFormat::Jira) => ftb::jira::write_table(table),
+        Some(OutputFormat::Mediawiki) => ftb::mediawiki::write_table(table),
+        Some(OutputFormat::Box) => ftb::box_drawing::write_table(
+            table,
+            &BoxOptions {
+                style: settings.box_style.unwrap_or_default().into(),
+                width_policy: settings.width.unwrap_or_default().into(),
+            },
+        ),
     };
 
-    let mut formatter = TableFormatter::new();
-    // Use format_document to handle full Markdown files with tables
-    let output = formatter.format_document(&input);
+    Ok(output)
+}
+
+fn run_command(
+    command: Command,
+    cli: &Cli,
+    configs: &mut ConfigCache,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::FromCsv {
+            input,
+            delimiter,
+            tsv,
+            no_header,
+        } => {
+            let options = CsvOptions {
+                delimiter: if tsv { '\t' } else { delimiter },
+                has_header: !no_header,
+            };
+            let input = read_input(input.as_deref())?;
+            print!("{}", ftb::csv::to_markdown(&input, &options)?);
+        }
+        Command::FromJson { input, columns } => {
+            let options = JsonImportOptions { columns };
+            let input = read_input(input.as_deref())?;
+            print!("{}", ftb::json::to_markdown(&input, &options)?);
+        }
+        Command::FromColumns { input, no_header } => {
+            let options = ColumnsOptions {
+                has_header: !no_header,
+            };
+            let input = read_input(input.as_deref())?;
+            print!("{}", ftb::columns::to_markdown(&input, &options)?);
+        }
+        #[cfg(feature = "sheet")]
+        Command::FromSheet {
+            input,
+            sheet,
+            range,
+        } => {
+            let settings = document_settings(cli, configs, Some(&input))?;
+            let options = ftb::sheet::SheetOptions {
+                sheet,
+                range,
+                width_policy: settings.width.unwrap_or_default().into(),
+            };
+            print!("{}", ftb::sheet::to_markdown(&input, &options)?);
+        }
+        Command::Edit {
+            operation,
+            offset,
+            input,
+        } => {
+            let settings = document_settings(cli, configs, input.as_deref())?;
+            let document = read_input(input.as_deref())?;
+            let edited = formatter(&settings).edit_table(&document, offset, operation.into())?;
+            let output = serde_json::json!({ "text": edited.text, "cursor": edited.cursor });
+            println!("{output}");
+        }
+        Command::Lsp => lsp::run()?,
+        Command::Config { show: _, path } => show_config(cli, configs, path.as_deref())?,
+    }
 
-    print!("{output}");
     Ok(())
 }
 
+/// Parses `START:END` 1-based inclusive line numbers into a 0-based, end
+/// exclusive pair.
+fn parse_line_range(value: &str) -> Result<(usize, usize), String> {
+    let (start, end) = parse_pair(value)
+        .filter(|&(start, end)| start <= end)
+        .ok_or_else(|| format!("invalid range {value:?}, expected START:END such as 10:24"))?;
+    Ok((start - 1, end))
+}
+
+/// Parses a 1-based `LINE:COL` into a 0-based position.
+fn parse_cursor(value: &str) -> Result<Position, String> {
+    let (line, column) = parse_pair(value)
+        .ok_or_else(|| format!("invalid cursor {value:?}, expected LINE:COL such as 12:7"))?;
+    Ok(Position {
+        line: line - 1,
+        column: column - 1,
+    })
+}
+
+/// Parses two positive numbers separated by a colon.
+fn parse_pair(value: &str) -> Option<(usize, usize)> {
+    let (first, second) = value.split_once(':')?;
+    let first: usize = first.trim().parse().ok()?;
+    let second: usize = second.trim().parse().ok()?;
+    (first > 0 && second > 0).then_some((first, second))
+}
+
+fn parse_delimiter(value: &str) -> Result<char, String> {
+    match value {
+        "tab" | "\\t" => Ok('\t'),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c != '"' && c != '\n' && c != '\r' => Ok(c),
+                _ => Err(format!(
+                    "invalid delimiter {value:?}, expected a single character"
+                )),
+            }
+        }
+    }
+}
+
+fn read_input(path: Option<&Path>) -> Result<String, Box<dyn std::error::Error>> {
+    const MAX_INPUT_SIZE: u64 = 10 * 1024 * 1024; // 10MB
+
+    if let Some(path) = path {
+        read_file(path, MAX_INPUT_SIZE)
+    } else {
+        read_stdin(MAX_INPUT_SIZE)
+    }
+}
+
 fn read_file(path: &Path, max_size: u64) -> Result<String, Box<dyn std::error::Error>> {
     let metadata = fs::metadata(path).map_err(|e| match e.kind() {
         ErrorKind::NotFound => {
@@ -0,0 +1,345 @@
+//! MediaWiki `{| ... |}` tables.
+//!
+//! ```text
+//! {| class="wikitable"
+//! ! Name !! Qty
+//! |-
+//! | tea || 1
+//! |}
+//! ```
+
+use crate::html::{attributes_alignment, parse_attributes};
+use crate::table::cell_lines;
+use crate::{Alignment, Result, Table, TableError};
+
+/// Parses every MediaWiki table in the input, in document order.
+///
+/// Cells may be written one per line or several on a line separated by `||`
+/// (`!!` for header cells), and lines that do not start with `|` or `!`
+/// continue the cell above. A first row made only of `!` cells becomes the
+/// header, with column alignment read from `align` attributes or
+/// `text-align` styles on its cells; otherwise the header is empty.
+/// `{{!}}` becomes a literal pipe, and captions are dropped.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there is no table, and
+/// `TableError::Unsupported` for `rowspan`, `colspan` or nested tables.
+///
+/// # Examples
+///
+/// ```
+/// let input = "{|\n! Page !! style=\"text-align:right\" | Views\n|-\n| [[Main|Home]] || 12\n|}";
+/// let tables = ftb::mediawiki::parse_tables(input).unwrap();
+/// assert_eq!(tables[0].header, vec!["Page", "Views"]);
+/// assert_eq!(tables[0].rows, vec![vec!["[[Main|Home]]", "12"]]);
+/// assert_eq!(tables[0].alignment(1), ftb::Alignment::Right);
+/// ```
+pub fn parse_tables(input: &str) -> Result<Vec<Table>> {
+    let mut tables = Vec::new();
+    let mut builder: Option<TableBuilder> = None;
+
+    for (line_i, line) in input.lines().map(str::trim).enumerate() {
+        let line_number = line_i + 1;
+
+        if line.starts_with("{|") {
+            if builder.is_some() {
+                return Err(TableError::Unsupported(format!(
+                    "nested table on line {line_number}"
+                )));
+            }
+            builder = Some(TableBuilder::default());
+            continue;
+        }
+        let Some(table) = builder.as_mut() else {
+            continue;
+        };
+
+        if line.starts_with("|}") {
+            tables.extend(builder.take().map(TableBuilder::finish));
+        } else if line.starts_with("|-") {
+            table.end_row();
+        } else if line.starts_with("|+") {
+            table.in_caption = true;
+        } else if let Some(cells) = line.strip_prefix('!') {
+            for cell in split_outside_links(cells, &["!!", "||"]) {
+                table.push_cell(cell, true, line_number)?;
+            }
+        } else if let Some(cells) = line.strip_prefix('|') {
+            for cell in split_outside_links(cells, &["||"]) {
+                table.push_cell(cell, false, line_number)?;
+            }
+        } else {
+            table.continue_cell(line);
+        }
+    }
+
+    if tables.is_empty() {
+        return Err(TableError::EmptyInput);
+    }
+
+    Ok(tables)
+}
+
+/// Converts the first MediaWiki table in the input into an aligned Markdown
+/// table.
+///
+/// # Errors
+///
+/// Returns `TableError` if no table can be parsed or formatted.
+pub fn to_markdown(input: &str) -> Result<String> {
+    let tables = parse_tables(input)?;
+    tables[0].to_markdown()
+}
+
+/// Renders a table as a MediaWiki `wikitable`.
+///
+/// Column alignment becomes a `text-align` style on every cell of the
+/// column, pipes in cell content become `{{!}}` and line breaks become
+/// `<br>`. A table whose header is entirely empty is written without a
+/// header row.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Name | Qty |\n|-|-:|\n| a\\|b | 1 |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::mediawiki::write_table(&table),
+///     "{| class=\"wikitable\"\n! Name !! style=\"text-align: right\" | Qty\n|-\n| a{{!}}b || style=\"text-align: right\" | 1\n|}\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table) -> String {
+    let has_header = table.header.iter().any(|cell| !cell.trim().is_empty());
+    let mut output = String::from("{| class=\"wikitable\"\n");
+
+    if has_header {
+        output.push_str(&format_row(table, &table.header, "!", " !! "));
+    }
+    for (row_i, row) in table.rows.iter().enumerate() {
+        if has_header || row_i > 0 {
+            output.push_str("|-\n");
+        }
+        output.push_str(&format_row(table, row, "|", " || "));
+    }
+    output.push_str("|}\n");
+
+    output
+}
+
+/// Formats one row on a single line, with alignment styles on its cells.
+fn format_row(table: &Table, row: &[String], marker: &str, separator: &str) -> String {
+    let cells: Vec<String> = (0..table.column_count())
+        .map(|col_i| {
+            let text = escape_cell(row.get(col_i).map_or("", String::as_str));
+            match table.alignment(col_i) {
+                Alignment::None => text,
+                alignment => {
+                    let value = match alignment {
+                        Alignment::Center => "center",
+                        Alignment::Right => "right",
+                        _ => "left",
+                    };
+                    format!("style=\"text-align: {value}\" | {text}")
+                }
+            }
+        })
+        .collect();
+
+    let line = format!("{marker} {}", cells.join(separator));
+    format!("{}\n", line.trim_end())
+}
+
+/// Escapes pipes as `{{!}}` and turns line breaks into `<br>`.
+fn escape_cell(cell: &str) -> String {
+    cell_lines(cell)
+        .iter()
+        .map(|line| line.replace('|', "{{!}}"))
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// A table being assembled from lines.
+#[derive(Default)]
+struct TableBuilder {
+    header: Option<Vec<String>>,
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    row_is_header: bool,
+    in_caption: bool,
+}
+
+impl TableBuilder {
+    fn push_cell(&mut self, cell: &str, is_header: bool, line_number: usize) -> Result<()> {
+        self.in_caption = false;
+
+        let (attributes, text) = match split_outside_links(cell, &["|"]).as_slice() {
+            [attributes, text, ..] => (parse_attributes(attributes), *text),
+            _ => (Vec::new(), cell),
+        };
+        for span in ["rowspan", "colspan"] {
+            if attributes
+                .iter()
+                .any(|(key, value)| key == span && value.trim() != "1")
+            {
+                return Err(TableError::Unsupported(format!(
+                    "{span} on line {line_number}"
+                )));
+            }
+        }
+
+        if self.row.is_empty() {
+            self.row_is_header = is_header;
+        }
+        self.row_is_header &= is_header;
+        if self.header.is_none() && self.rows.is_empty() {
+            self.alignments.push(attributes_alignment(&attributes));
+        }
+        self.row.push(text.trim().replace("{{!}}", "|"));
+        Ok(())
+    }
+
+    fn continue_cell(&mut self, line: &str) {
+        if self.in_caption {
+            return;
+        }
+        if let Some(cell) = self.row.last_mut() {
+            if !cell.is_empty() {
+                cell.push('\n');
+            }
+            cell.push_str(&line.replace("{{!}}", "|"));
+        }
+    }
+
+    fn end_row(&mut self) {
+        self.in_caption = false;
+        if self.row.is_empty() {
+            return;
+        }
+
+        let row = std::mem::take(&mut self.row);
+        if self.row_is_header && self.header.is_none() && self.rows.is_empty() {
+            self.header = Some(row);
+        } else {
+            self.rows.push(row);
+        }
+    }
+
+    fn finish(mut self) -> Table {
+        self.end_row();
+
+        let (header, alignments) = match self.header {
+            Some(header) => (header, self.alignments),
+            None => {
+                let num_columns = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+                (vec![String::new(); num_columns], Vec::new())
+            }
+        };
+        let mut table = Table::new(header, self.rows);
+        if !alignments.is_empty() {
+            table.alignments = alignments;
+        }
+        table
+    }
+}
+
+/// Splits text on any of the separators, except inside `[[links]]`,
+/// `[external links]` and `{{templates}}`.
+fn split_outside_links<'a>(text: &'a str, separators: &[&str]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with("{{") || rest.starts_with("[[") {
+            depth += 1;
+            i += 2;
+        } else if rest.starts_with("}}") || rest.starts_with("]]") {
+            depth = depth.saturating_sub(1);
+            i += 2;
+        } else if let Some(separator) = separators
+            .iter()
+            .find(|separator| depth == 0 && rest.starts_with(**separator))
+        {
+            parts.push(&text[start..i]);
+            i += separator.len();
+            start = i;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    parts.push(&text[start..]);
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_cell_per_line_and_caption() {
+        let input = "\
+{| class=\"wikitable\"
+|+ Prices
+spanning lines
+|-
+! Item
+! Cost
+|-
+| tea
+| 1
+|-
+| cake
+| two
+lines
+|}";
+        let tables = parse_tables(input).expect("Should parse");
+
+        assert_eq!(tables[0].header, vec!["Item", "Cost"]);
+        assert_eq!(
+            tables[0].rows,
+            vec![vec!["tea", "1"], vec!["cake", "two\nlines"]]
+        );
+    }
+
+    #[test]
+    fn test_headerless_round_trip() {
+        let table = Table::new(
+            vec![String::new(), String::new()],
+            vec![
+                vec!["a".to_string(), "x|y".to_string()],
+                vec!["b".to_string(), String::new()],
+            ],
+        );
+        let output = write_table(&table);
+
+        assert_eq!(
+            output,
+            "{| class=\"wikitable\"\n| a || x{{!}}y\n|-\n| b ||\n|}\n"
+        );
+        assert_eq!(parse_tables(&output).expect("Should parse")[0], table);
+    }
+
+    #[test]
+    fn test_rejects_spans_and_nested_tables() {
+        let span = "{|\n! a\n|-\n| colspan=\"2\" | x\n|}";
+        let nested = "{|\n| a\n{|\n| b\n|}\n|}";
+
+        assert!(matches!(
+            parse_tables(span),
+            Err(TableError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse_tables(nested),
+            Err(TableError::Unsupported(_))
+        ));
+    }
+}
@@ -0,0 +1,408 @@
+//! Org-mode tables: `|---+---|` hlines, `<r>`/`<l>`/`<c>`/`<10>` alignment
+//! cookies, and Org's right alignment of numeric columns.
+
+use crate::table::pad_cell;
+use crate::{
+    replace_tables, Alignment, LocatedError, Result, Table, TableError, TableScan, TableSpan,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// A parsed line of an Org table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OrgLine {
+    /// A horizontal rule such as `|---+---|`.
+    Hline,
+
+    /// A row of alignment and width cookies such as `| <l> | <r10> |`.
+    Cookies(Vec<String>),
+
+    /// A row of cells.
+    Cells(Vec<String>),
+}
+
+/// Formats the Org tables in a document, preserving all other content.
+///
+/// Source blocks (`#+BEGIN_SRC` … `#+END_SRC`) and example blocks are left
+/// untouched, as are `#+TBLFM:` lines following a table.
+///
+/// # Examples
+///
+/// ```
+/// let doc = "* Notes\n| a | bb |\n|-+-|\n| 10 | x |\n";
+/// assert_eq!(
+///     ftb::org::format_document(doc),
+///     "* Notes\n|  a | bb |\n|----+----|\n| 10 | x  |\n"
+/// );
+/// ```
+#[must_use]
+pub fn format_document(document: &str) -> String {
+    replace_tables(document, &scan_tables(document).spans)
+}
+
+/// Finds the Org tables in a document along with their formatted text, or
+/// the reason they could not be formatted.
+pub(crate) fn scan_tables(document: &str) -> TableScan {
+    let lines: Vec<&str> = document.lines().collect();
+    let mut scan = TableScan::default();
+    let mut in_block = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let keyword = line.trim_start().to_ascii_lowercase();
+
+        if keyword.starts_with("#+begin_") {
+            in_block = true;
+        } else if keyword.starts_with("#+end_") {
+            in_block = false;
+        } else if !in_block && is_table_line(line) {
+            let mut end = i;
+            while end < lines.len() && is_table_line(lines[end]) {
+                end += 1;
+            }
+            match format_located_table(&lines[i..end].join("\n")) {
+                Ok(formatted) => {
+                    scan.push_span(TableSpan {
+                        start_line: i,
+                        end_line: end,
+                        formatted,
+                    });
+                    i = end;
+                    continue;
+                }
+                Err(error) => scan.push_diagnostic(i, end, error),
+            }
+        }
+
+        i += 1;
+    }
+
+    scan
+}
+
+/// Realigns a single Org table, keeping its hlines and cookie rows in place.
+///
+/// Column alignment comes from an `<l>`, `<c>` or `<r>` cookie when present;
+/// otherwise, as in Org, columns where most values below the header are
+/// numbers are right aligned and the rest are left aligned.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if the input has no table rows, or
+/// `TableError::InvalidStructure` if a line is not part of an Org table.
+pub fn format_table(table: &str) -> Result<String> {
+    Ok(format_located_table(table)?)
+}
+
+/// Realigns a single Org table, keeping the location of any error.
+fn format_located_table(table: &str) -> std::result::Result<String, LocatedError> {
+    let lines: Vec<&str> = table.lines().collect();
+    let first = lines.first().ok_or(TableError::EmptyInput)?;
+    let indent = &first[..first.len() - first.trim_start().len()];
+
+    Ok(render_lines(&parse_lines(&lines)?, indent)?)
+}
+
+/// Parses an Org table into a [`Table`].
+///
+/// The first row is the header. Hlines are dropped, and cookie rows become
+/// column alignments.
+///
+/// # Errors
+///
+/// Returns `TableError::EmptyInput` if there are no rows, or
+/// `TableError::InvalidStructure` if a line is not part of an Org table.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::Alignment;
+///
+/// let table = ftb::org::parse_table("| a | b |\n|---+---|\n| <r> | |\n| 1 | 2 |").unwrap();
+/// assert_eq!(table.header, vec!["a", "b"]);
+/// assert_eq!(table.rows, vec![vec!["1", "2"]]);
+/// assert_eq!(table.alignment(0), Alignment::Right);
+/// ```
+pub fn parse_table(table: &str) -> Result<Table> {
+    let lines: Vec<&str> = table
+        .lines()
+        .skip_while(|line| !is_table_line(line))
+        .take_while(|line| is_table_line(line))
+        .collect();
+    let parsed = parse_lines(&lines)?;
+
+    let mut rows = parsed.iter().filter_map(|line| match line {
+        OrgLine::Cells(cells) => Some(cells.clone()),
+        _ => None,
+    });
+    let header = rows.next().ok_or(TableError::EmptyInput)?;
+    let rows: Vec<Vec<String>> = rows.collect();
+
+    let mut table = Table::new(header, rows);
+    let num_columns = table.column_count();
+    table.alignments = parsed
+        .iter()
+        .find_map(|line| match line {
+            OrgLine::Cookies(cookies) => Some(
+                (0..num_columns)
+                    .map(|col_i| cookie_alignment(cookies.get(col_i).map_or("", String::as_str)))
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    Ok(table)
+}
+
+/// Renders a table as an Org table, with an hline under the header and a
+/// cookie row for any explicit column alignment.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Name | Qty |\n|-|-:|\n| tea | 1 |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::org::write_table(&table),
+///     "| Name | Qty |\n|------+-----|\n|      | <r> |\n| tea  |   1 |\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table) -> String {
+    // Org has no escape for `|` inside a cell, only the \vert entity
+    let cells = |row: &[String]| {
+        row.iter()
+            .map(|cell| cell.replace('|', "\\vert{}"))
+            .collect()
+    };
+
+    let mut lines = vec![OrgLine::Cells(cells(&table.header)), OrgLine::Hline];
+    let cookies: Vec<String> = (0..table.column_count())
+        .map(|col_i| match table.alignment(col_i) {
+            Alignment::None => String::new(),
+            Alignment::Left => "<l>".to_string(),
+            Alignment::Center => "<c>".to_string(),
+            Alignment::Right => "<r>".to_string(),
+        })
+        .collect();
+    if cookies.iter().any(|cookie| !cookie.is_empty()) {
+        lines.push(OrgLine::Cookies(cookies));
+    }
+    lines.extend(table.rows.iter().map(|row| OrgLine::Cells(cells(row))));
+
+    render_lines(&lines, "").unwrap_or_default()
+}
+
+/// Renders parsed lines with every column padded to its widest cell.
+fn render_lines(lines: &[OrgLine], indent: &str) -> Result<String> {
+    let num_columns = lines
+        .iter()
+        .map(|line| match line {
+            OrgLine::Hline => 0,
+            OrgLine::Cookies(cells) | OrgLine::Cells(cells) => cells.len(),
+        })
+        .max()
+        .unwrap_or(0);
+    if num_columns == 0 {
+        return Err(TableError::EmptyInput);
+    }
+
+    let mut widths = vec![1; num_columns];
+    for line in lines {
+        if let OrgLine::Cookies(cells) | OrgLine::Cells(cells) = line {
+            for (col_i, cell) in cells.iter().enumerate() {
+                widths[col_i] = widths[col_i].max(cell.width());
+            }
+        }
+    }
+    let alignments = column_alignments(lines, num_columns);
+
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(indent);
+        match line {
+            OrgLine::Hline => {
+                let rules: Vec<String> =
+                    widths.iter().map(|&width| "-".repeat(width + 2)).collect();
+                output.push('|');
+                output.push_str(&rules.join("+"));
+                output.push('|');
+            }
+            OrgLine::Cookies(cells) | OrgLine::Cells(cells) => {
+                output.push('|');
+                for (col_i, &width) in widths.iter().enumerate() {
+                    let cell = cells.get(col_i).map_or("", String::as_str);
+                    output.push(' ');
+                    output.push_str(&pad_cell(cell, width, alignments[col_i]));
+                    output.push_str(" |");
+                }
+            }
+        }
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// Checks if a line belongs to an Org table.
+fn is_table_line(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+/// Splits table lines into hlines, cookie rows and cell rows.
+fn parse_lines(lines: &[&str]) -> std::result::Result<Vec<OrgLine>, LocatedError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(line_i, line)| {
+            let content = line.trim();
+            if !content.starts_with('|') {
+                return Err(LocatedError::at_line(
+                    TableError::InvalidStructure(format!(
+                        "Line {} is not part of an Org table",
+                        line_i + 1
+                    )),
+                    line_i,
+                    line,
+                ));
+            }
+            if content.starts_with("|-") {
+                return Ok(OrgLine::Hline);
+            }
+
+            let inner = content[1..].strip_suffix('|').unwrap_or(&content[1..]);
+            let cells: Vec<String> = inner
+                .split('|')
+                .map(|cell| cell.trim().to_string())
+                .collect();
+            let is_cookie_row = cells.iter().any(|cell| is_cookie(cell))
+                && cells.iter().all(|cell| cell.is_empty() || is_cookie(cell));
+
+            Ok(if is_cookie_row {
+                OrgLine::Cookies(cells)
+            } else {
+                OrgLine::Cells(cells)
+            })
+        })
+        .collect()
+}
+
+/// Checks for an alignment/width cookie: `<l>`, `<c>`, `<r>`, `<10>` or `<r10>`.
+fn is_cookie(cell: &str) -> bool {
+    let Some(inner) = cell
+        .strip_prefix('<')
+        .and_then(|cell| cell.strip_suffix('>'))
+    else {
+        return false;
+    };
+    let digits = inner.strip_prefix(['l', 'c', 'r']).unwrap_or(inner);
+    !inner.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns the alignment a cookie requests, if any.
+fn cookie_alignment(cookie: &str) -> Alignment {
+    match cookie
+        .strip_prefix('<')
+        .and_then(|cookie| cookie.chars().next())
+    {
+        Some('l') => Alignment::Left,
+        Some('c') => Alignment::Center,
+        Some('r') => Alignment::Right,
+        _ => Alignment::None,
+    }
+}
+
+/// Decides each column's alignment from cookies, falling back to Org's
+/// rule of right-aligning columns where most non-empty body cells are numbers.
+fn column_alignments(lines: &[OrgLine], num_columns: usize) -> Vec<Alignment> {
+    // Header rows above the first hline don't count towards the numbers
+    let body_start = lines
+        .iter()
+        .position(|line| *line == OrgLine::Hline)
+        .map_or(0, |hline| hline + 1);
+
+    (0..num_columns)
+        .map(|col_i| {
+            let cookie = lines.iter().find_map(|line| match line {
+                OrgLine::Cookies(cells) => Some(cookie_alignment(
+                    cells.get(col_i).map_or("", String::as_str),
+                )),
+                _ => None,
+            });
+            if let Some(alignment) = cookie.filter(|alignment| *alignment != Alignment::None) {
+                return alignment;
+            }
+
+            let values: Vec<&str> = lines[body_start..]
+                .iter()
+                .filter_map(|line| match line {
+                    OrgLine::Cells(cells) => cells.get(col_i).map(String::as_str),
+                    _ => None,
+                })
+                .filter(|cell| !cell.is_empty())
+                .collect();
+            let numbers = values.iter().filter(|cell| is_number(cell)).count();
+
+            if numbers > 0 && numbers * 2 > values.len() {
+                Alignment::Right
+            } else {
+                Alignment::Left
+            }
+        })
+        .collect()
+}
+
+/// Checks if a cell holds a number, optionally with a trailing `%`.
+fn is_number(cell: &str) -> bool {
+    let value = cell.strip_suffix('%').unwrap_or(cell);
+    value.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table_hlines_and_cookies() {
+        let input = "  | Item | Price | Note |\n  |-\n  | <c> | | <r> |\n  | tea | 2.5 | x |\n  |---+--|\n  | total | 10 |";
+
+        assert_eq!(
+            format_table(input).expect("Should format"),
+            "  | Item  | Price | Note |\n  |-------+-------+------|\n  |  <c>  |       |  <r> |\n  |  tea  |   2.5 |    x |\n  |-------+-------+------|\n  | total |    10 |      |\n"
+        );
+    }
+
+    #[test]
+    fn test_mostly_text_column_is_left_aligned() {
+        let input = "| a |\n| 1 |\n| x |\n| y |";
+
+        assert_eq!(
+            format_table(input).expect("Should format"),
+            "| a |\n| 1 |\n| x |\n| y |\n"
+        );
+    }
+
+    #[test]
+    fn test_format_document_skips_source_blocks() {
+        let input =
+            "#+BEGIN_SRC text\n| a | bbb |\n#+END_SRC\n| a | bbb |\n| ccc | d |\n#+TBLFM: $2=$1\n";
+
+        assert_eq!(
+            format_document(input),
+            "#+BEGIN_SRC text\n| a | bbb |\n#+END_SRC\n| a   | bbb |\n| ccc | d   |\n#+TBLFM: $2=$1\n"
+        );
+    }
+
+    #[test]
+    fn test_is_cookie() {
+        assert!(is_cookie("<r>"));
+        assert!(is_cookie("<10>"));
+        assert!(is_cookie("<l12>"));
+        assert!(!is_cookie("<>"));
+        assert!(!is_cookie("<b>"));
+    }
+}
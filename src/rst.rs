@@ -0,0 +1,690 @@
+//! reStructuredText tables: rendering grid tables and list tables, and
+//! realigning the grid and simple tables found in `.rst` documents.
+
+use crate::table::{cell_lines, pad_cell};
+use crate::{
+    replace_tables, Alignment, LocatedError, Result, Table, TableError, TableScan, TableSpan,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Renders a table as a reST grid table.
+///
+/// Grid tables have no alignment syntax, so column alignment is carried over
+/// by padding the cell text. Line breaks in cells become separate lines
+/// within the cell.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Name | Qty |\n|-|-:|\n| tea | 1 |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::rst::write_grid_table(&table),
+///     "+------+-----+\n| Name | Qty |\n+======+=====+\n| tea  |   1 |\n+------+-----+\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_grid_table(table: &Table) -> String {
+    let num_columns = table.column_count();
+    let rows: Vec<Vec<Vec<&str>>> = std::iter::once(&table.header)
+        .chain(&table.rows)
+        .map(|row| {
+            (0..num_columns)
+                .map(|col_i| cell_lines(row.get(col_i).map_or("", String::as_str)))
+                .collect()
+        })
+        .collect();
+
+    let mut widths = vec![1; num_columns];
+    for row in &rows {
+        for (col_i, lines) in row.iter().enumerate() {
+            for line in lines {
+                widths[col_i] = widths[col_i].max(line.width());
+            }
+        }
+    }
+
+    let mut output = border(&widths, '-');
+    for (row_i, row) in rows.iter().enumerate() {
+        let height = row.iter().map(Vec::len).max().unwrap_or(1);
+        for line_i in 0..height {
+            output.push('|');
+            for (col_i, lines) in row.iter().enumerate() {
+                let text = lines.get(line_i).copied().unwrap_or("");
+                output.push(' ');
+                output.push_str(&pad_cell(text, widths[col_i], table.alignment(col_i)));
+                output.push_str(" |");
+            }
+            output.push('\n');
+        }
+        output.push_str(&border(&widths, if row_i == 0 { '=' } else { '-' }));
+    }
+
+    output
+}
+
+/// Renders a table as a reST `list-table` directive with one header row.
+///
+/// List tables have no per-column alignment, so alignment is not carried
+/// over. Cells with line breaks are written as line blocks.
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Name | Qty |\n|-|-|\n| tea | 1 |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::rst::write_list_table(&table),
+///     ".. list-table::\n   :header-rows: 1\n\n   * - Name\n     - Qty\n   * - tea\n     - 1\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_list_table(table: &Table) -> String {
+    let num_columns = table.column_count();
+    let mut output = String::from(".. list-table::\n   :header-rows: 1\n\n");
+
+    for row in std::iter::once(&table.header).chain(&table.rows) {
+        for col_i in 0..num_columns {
+            let lines = cell_lines(row.get(col_i).map_or("", String::as_str));
+            output.push_str(if col_i == 0 { "   * -" } else { "     -" });
+
+            if lines.len() == 1 {
+                if !lines[0].is_empty() {
+                    output.push(' ');
+                    output.push_str(lines[0]);
+                }
+                output.push('\n');
+                continue;
+            }
+
+            for (line_i, line) in lines.iter().enumerate() {
+                if line_i > 0 {
+                    output.push_str("      ");
+                }
+                output.push_str(" | ");
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+    }
+
+    output
+}
+
+/// Formats the grid and simple tables in a reST document, preserving all other content.
+///
+/// Borders are realigned to the widest cell of each column using display
+/// width, so CJK text and emoji line up. Tables with merged cells, tables
+/// that cannot be split into consistent columns, and anything inside literal
+/// blocks (`::`) are left untouched.
+///
+/// # Examples
+///
+/// ```
+/// let doc = "Title\n=====\n\n+---+---+\n| a | bbb |\n+===+===+\n| ccc | d |\n+---+---+\n";
+/// assert_eq!(
+///     ftb::rst::format_document(doc),
+///     "Title\n=====\n\n+-----+-----+\n| a   | bbb |\n+=====+=====+\n| ccc | d   |\n+-----+-----+\n"
+/// );
+/// ```
+#[must_use]
+pub fn format_document(document: &str) -> String {
+    replace_tables(document, &scan_tables(document).spans)
+}
+
+/// Finds the grid and simple tables in a reST document along with their
+/// formatted text.
+///
+/// Grid tables that cannot be formatted are reported. Simple tables that
+/// cannot be formatted are not, since their `=====` borders look the same as
+/// section title underlines.
+pub(crate) fn scan_tables(document: &str) -> TableScan {
+    let lines: Vec<&str> = document.lines().collect();
+    let mut scan = TableScan::default();
+    let mut literal_indent: Option<usize> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let indent = indentation(line);
+
+        // Skip the indented body of a literal block introduced by `::`
+        if let Some(block_indent) = literal_indent {
+            if line.trim().is_empty() || indent > block_indent {
+                i += 1;
+                continue;
+            }
+            literal_indent = None;
+        }
+
+        let formatted = if is_grid_border(line) {
+            grid_table_end(&lines, i).and_then(|end| {
+                match format_located_grid_table(&lines[i..end].join("\n")) {
+                    Ok(table) => Some((end, table)),
+                    Err(error) => {
+                        scan.push_diagnostic(i, end, error);
+                        None
+                    }
+                }
+            })
+        } else if is_simple_border(line) {
+            simple_table_end(&lines, i).and_then(|end| {
+                format_simple_table(&lines[i..end].join("\n"))
+                    .ok()
+                    .map(|table| (end, table))
+            })
+        } else {
+            None
+        };
+
+        if let Some((end, formatted)) = formatted {
+            scan.push_span(TableSpan {
+                start_line: i,
+                end_line: end,
+                formatted,
+            });
+            i = end;
+            continue;
+        }
+
+        if line.trim_end().ends_with("::") {
+            literal_indent = Some(indent);
+        }
+        i += 1;
+    }
+
+    scan
+}
+
+/// Realigns a single reST grid table.
+///
+/// Every line keeps its original indentation and the header separator
+/// (`+===+`) stays in place.
+///
+/// # Errors
+///
+/// Returns `TableError::InvalidStructure` if the lines are not a grid table
+/// with the same number of columns on every line, and
+/// `TableError::Unsupported` if the table has merged cells.
+pub fn format_grid_table(table: &str) -> Result<String> {
+    Ok(format_located_grid_table(table)?)
+}
+
+/// Realigns a single reST grid table, keeping the location of any error.
+fn format_located_grid_table(table: &str) -> std::result::Result<String, LocatedError> {
+    let lines: Vec<&str> = table.lines().collect();
+    let first = lines
+        .first()
+        .filter(|line| is_grid_border(line))
+        .ok_or_else(|| {
+            TableError::InvalidStructure("Grid table must start with a border".to_string())
+        })?;
+    let indent = &first[..indentation(first)];
+    let boundaries = border_columns(first.trim());
+    let num_columns = boundaries.len() - 1;
+
+    let mut parsed = Vec::with_capacity(lines.len());
+    for (line_i, line) in lines.iter().enumerate() {
+        let content = line.trim();
+        if is_grid_border(line) {
+            let segments: Vec<&str> = content[1..content.len() - 1].split('+').collect();
+            if segments.len() != num_columns {
+                return Err(LocatedError::at_line(
+                    TableError::Unsupported(format!(
+                        "merged cells in grid table border on line {}",
+                        line_i + 1
+                    )),
+                    line_i,
+                    line,
+                ));
+            }
+            parsed.push(TableLine::Border(if content.contains('=') {
+                '='
+            } else {
+                '-'
+            }));
+        } else if content.starts_with('|') && content.ends_with('|') {
+            let cells = split_grid_line(content, &boundaries, num_columns).ok_or_else(|| {
+                LocatedError::at_line(
+                    TableError::Unsupported(format!(
+                        "merged or misaligned cells in grid table on line {}",
+                        line_i + 1
+                    )),
+                    line_i,
+                    line,
+                )
+            })?;
+            parsed.push(TableLine::Cells(cells));
+        } else {
+            return Err(LocatedError::at_line(
+                TableError::InvalidStructure(format!(
+                    "Line {} is not part of a grid table",
+                    line_i + 1
+                )),
+                line_i,
+                line,
+            ));
+        }
+    }
+
+    let widths = column_widths(&parsed, num_columns);
+    let mut output = String::new();
+    for line in &parsed {
+        output.push_str(indent);
+        match line {
+            TableLine::Border(fill) => output.push_str(&border(&widths, *fill)),
+            TableLine::Blank => output.push('\n'),
+            TableLine::Cells(cells) => {
+                output.push('|');
+                for (col_i, cell) in cells.iter().enumerate() {
+                    output.push(' ');
+                    output.push_str(&pad_cell(cell, widths[col_i], Alignment::None));
+                    output.push_str(" |");
+                }
+                output.push('\n');
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Realigns a single reST simple table (`=====  =====` borders).
+///
+/// # Errors
+///
+/// Returns `TableError::InvalidStructure` if a line cannot be split into the
+/// columns declared by the top border, and `TableError::Unsupported` if the
+/// table uses column spans.
+pub fn format_simple_table(table: &str) -> Result<String> {
+    let lines: Vec<&str> = table.lines().collect();
+    let first = lines
+        .first()
+        .filter(|line| is_simple_border(line))
+        .ok_or_else(|| {
+            TableError::InvalidStructure("Simple table must start with a border".to_string())
+        })?;
+    let indent_len = indentation(first);
+    let indent = &first[..indent_len];
+    let columns = border_runs(&first[indent_len..], '=');
+    let num_columns = columns.len();
+
+    let mut parsed = Vec::with_capacity(lines.len());
+    for (line_i, line) in lines.iter().enumerate() {
+        if is_simple_border(line) {
+            if border_runs(line.get(indent_len..).unwrap_or(""), '=').len() != num_columns {
+                return Err(TableError::InvalidStructure(format!(
+                    "Border on line {} does not match the table columns",
+                    line_i + 1
+                )));
+            }
+            parsed.push(TableLine::Border('='));
+        } else if line.trim().is_empty() {
+            parsed.push(TableLine::Blank);
+        } else if line.trim().chars().all(|c| c == '-' || c == ' ') {
+            return Err(TableError::Unsupported(format!(
+                "column span on line {}",
+                line_i + 1
+            )));
+        } else {
+            let text = line.get(indent_len..).unwrap_or_else(|| line.trim_start());
+            let cells = split_simple_line(text, &columns).ok_or_else(|| {
+                TableError::InvalidStructure(format!(
+                    "Line {} does not fit the table columns",
+                    line_i + 1
+                ))
+            })?;
+            parsed.push(TableLine::Cells(cells));
+        }
+    }
+
+    let widths = column_widths(&parsed, num_columns);
+    let mut output = String::new();
+    for line in &parsed {
+        let text = match line {
+            TableLine::Border(fill) => widths
+                .iter()
+                .map(|&width| fill.to_string().repeat(width))
+                .collect::<Vec<_>>()
+                .join("  "),
+            TableLine::Blank => String::new(),
+            TableLine::Cells(cells) => cells
+                .iter()
+                .enumerate()
+                .map(|(col_i, cell)| pad_cell(cell, widths[col_i], Alignment::None))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string(),
+        };
+        if !text.is_empty() {
+            output.push_str(indent);
+        }
+        output.push_str(&text);
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// A parsed line of a grid or simple table.
+enum TableLine {
+    /// A border, drawn with the given fill character.
+    Border(char),
+
+    /// A blank line inside a simple table.
+    Blank,
+
+    /// Cell texts, one per column.
+    Cells(Vec<String>),
+}
+
+/// Widest cell of each column, at least 1.
+fn column_widths(lines: &[TableLine], num_columns: usize) -> Vec<usize> {
+    let mut widths = vec![1; num_columns];
+    for line in lines {
+        if let TableLine::Cells(cells) = line {
+            for (col_i, cell) in cells.iter().enumerate() {
+                widths[col_i] = widths[col_i].max(cell.width());
+            }
+        }
+    }
+    widths
+}
+
+/// Number of leading whitespace bytes.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Checks for a grid border such as `+----+====+`.
+fn is_grid_border(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 3
+        && (line.starts_with("+-") || line.starts_with("+="))
+        && line.ends_with('+')
+        && line.chars().all(|c| matches!(c, '+' | '-' | '='))
+}
+
+/// Checks for a simple table border with at least two columns, e.g. `===  ===`.
+///
+/// A single run of `=` is a section title underline, not a table.
+fn is_simple_border(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('=')
+        && line.chars().all(|c| c == '=' || c == ' ')
+        && border_runs(line, '=').len() >= 2
+}
+
+/// Finds the end of a grid table whose top border is at `start`.
+fn grid_table_end(lines: &[&str], start: usize) -> Option<usize> {
+    let mut end = start + 1;
+    let mut last_border = None;
+
+    while end < lines.len() {
+        let line = lines[end].trim();
+        if is_grid_border(line) {
+            last_border = Some(end);
+        } else if !line.starts_with('|') {
+            break;
+        }
+        end += 1;
+    }
+
+    last_border.map(|last| last + 1)
+}
+
+/// Finds the end of a simple table whose top border is at `start`.
+///
+/// The table ends at a border followed by a blank line or the end of input,
+/// and has at most three borders (top, header separator, bottom).
+fn simple_table_end(lines: &[&str], start: usize) -> Option<usize> {
+    let mut borders = 1;
+
+    for i in start + 1..lines.len() {
+        if !is_simple_border(lines[i]) {
+            continue;
+        }
+        borders += 1;
+
+        let at_end = lines.get(i + 1).is_none_or(|next| next.trim().is_empty());
+        if at_end {
+            return Some(i + 1);
+        }
+        if borders == 3 {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Display columns of each `+` in a grid border.
+fn border_columns(border: &str) -> Vec<usize> {
+    border
+        .char_indices()
+        .filter(|(_, c)| *c == '+')
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Column ranges (start, end) of each run of `fill` in a simple table
+/// border such as `===  ===`.
+pub(crate) fn border_runs(border: &str, fill: char) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+
+    for (i, c) in border.char_indices() {
+        match (c, start) {
+            (c, None) if c == fill => start = Some(i),
+            (' ', Some(run_start)) => {
+                runs.push((run_start, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(run_start) = start {
+        runs.push((run_start, border.trim_end().len()));
+    }
+
+    runs
+}
+
+/// Splits a grid content line into cell texts.
+///
+/// Cells are first cut at the display columns of the border's `+` marks,
+/// which keeps any `|` inside cell text. If the line has been edited and no
+/// longer lines up, it is split on `|` instead, provided that yields the
+/// expected number of cells.
+fn split_grid_line(line: &str, boundaries: &[usize], num_columns: usize) -> Option<Vec<String>> {
+    let clean = |cell: &str| {
+        let cell = cell.strip_prefix(' ').unwrap_or(cell);
+        cell.trim_end().to_string()
+    };
+
+    if let Some(cells) = split_at_display_columns(line, boundaries) {
+        return Some(cells.iter().map(|cell| clean(cell)).collect());
+    }
+
+    let inner = &line[1..line.len() - 1];
+    let cells: Vec<&str> = inner.split('|').collect();
+    (cells.len() == num_columns).then(|| cells.iter().map(|cell| clean(cell)).collect())
+}
+
+/// Cuts `line` at the given display columns, each of which must hold a `|`.
+pub(crate) fn split_at_display_columns<'a>(
+    line: &'a str,
+    boundaries: &[usize],
+) -> Option<Vec<&'a str>> {
+    let mut cells = Vec::new();
+    let mut column = 0;
+    let mut next = 0;
+    let mut cell_start = 0;
+
+    for (i, c) in line.char_indices() {
+        if next < boundaries.len() && column == boundaries[next] {
+            if c != '|' {
+                return None;
+            }
+            if next > 0 {
+                cells.push(&line[cell_start..i]);
+            }
+            cell_start = i + 1;
+            next += 1;
+        } else if next < boundaries.len() && column > boundaries[next] {
+            return None;
+        }
+        column += c.width().unwrap_or(0);
+    }
+
+    (next == boundaries.len() && cell_start == line.len()).then_some(cells)
+}
+
+/// Splits a simple table line into one text per column.
+///
+/// Text is assigned to columns by display position, as long as the gaps
+/// between columns are blank. Otherwise the line has been edited out of
+/// alignment, and it is split on runs of two or more spaces instead, provided
+/// that yields exactly one text per column.
+pub(crate) fn split_simple_line(line: &str, columns: &[(usize, usize)]) -> Option<Vec<String>> {
+    if let Some(cells) = split_simple_by_position(line, columns) {
+        return Some(cells);
+    }
+
+    let cells: Vec<String> = line
+        .split("  ")
+        .map(str::trim)
+        .filter(|cell| !cell.is_empty())
+        .map(str::to_string)
+        .collect();
+    (cells.len() == columns.len()).then_some(cells)
+}
+
+/// Assigns each character to the column it falls under. The last column
+/// takes the rest of the line.
+fn split_simple_by_position(line: &str, columns: &[(usize, usize)]) -> Option<Vec<String>> {
+    let mut cells = vec![String::new(); columns.len()];
+    let mut column = 0;
+
+    for c in line.chars() {
+        match columns.iter().rposition(|&(start, _)| column >= start) {
+            None if c != ' ' => return None,
+            None => {}
+            Some(col_i) => {
+                let in_gap = column >= columns[col_i].1 && col_i + 1 < columns.len();
+                if in_gap && c != ' ' {
+                    return None;
+                }
+                cells[col_i].push(c);
+            }
+        }
+        column += c.width().unwrap_or(0);
+    }
+
+    Some(cells.iter().map(|cell| cell.trim().to_string()).collect())
+}
+
+/// Builds a `+---+---+` border line using `fill` for the horizontal rule.
+fn border(widths: &[usize], fill: char) -> String {
+    let mut line = String::from("+");
+    for &width in widths {
+        line.extend(std::iter::repeat_n(fill, width + 2));
+        line.push('+');
+    }
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Alignment;
+
+    fn table(header: &[&str], rows: &[&[&str]]) -> Table {
+        let strings = |cells: &[&str]| cells.iter().map(|cell| (*cell).to_string()).collect();
+        Table::new(
+            strings(header),
+            rows.iter().map(|row| strings(row)).collect(),
+        )
+    }
+
+    #[test]
+    fn test_grid_table_multi_line_cells() {
+        let mut table = table(&["a", "b"], &[&["one<br>two", "x"]]);
+        table.alignments = vec![Alignment::None, Alignment::Center];
+
+        assert_eq!(
+            write_grid_table(&table),
+            "+-----+---+\n| a   | b |\n+=====+===+\n| one | x |\n| two |   |\n+-----+---+\n"
+        );
+    }
+
+    #[test]
+    fn test_format_grid_table_keeps_pipes_in_aligned_cells() {
+        let input = "+-----+---+\n| a|b | c |\n+=====+===+\n| d   | eeee |\n+-----+---+";
+
+        assert_eq!(
+            format_grid_table(input).expect("Should format"),
+            "+-----+------+\n| a|b | c    |\n+=====+======+\n| d   | eeee |\n+-----+------+\n"
+        );
+    }
+
+    #[test]
+    fn test_format_grid_table_unicode_width() {
+        let input = "  +---+---+\n  | 你好 | x |\n  +---+---+";
+
+        assert_eq!(
+            format_grid_table(input).expect("Should format"),
+            "  +------+---+\n  | 你好 | x |\n  +------+---+\n"
+        );
+    }
+
+    #[test]
+    fn test_format_grid_table_rejects_merged_cells() {
+        let input = "+---+---+\n| a | b |\n+---+---+\n| spanning  |\n+-------+\n";
+
+        assert!(matches!(
+            format_grid_table(input),
+            Err(TableError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_simple_table() {
+        let input =
+            "=====  =====\nA      B\n=====  =====\nlonger text  x\n\nTrue   y\n=====  =====";
+
+        assert_eq!(
+            format_simple_table(input).expect("Should format"),
+            "===========  =\nA            B\n===========  =\nlonger text  x\n\nTrue         y\n===========  =\n"
+        );
+    }
+
+    #[test]
+    fn test_format_document_skips_literal_blocks_and_titles() {
+        let input =
+            "Title\n=====\n\nExample::\n\n   +-+\n   | wide |\n   +-+\n\n+-+\n| wide |\n+-+\n";
+
+        assert_eq!(
+            format_document(input),
+            "Title\n=====\n\nExample::\n\n   +-+\n   | wide |\n   +-+\n\n+------+\n| wide |\n+------+\n"
+        );
+    }
+
+    #[test]
+    fn test_list_table_line_blocks_and_empty_cells() {
+        let table = table(&["a", "b"], &[&["one<br>two"]]);
+
+        assert_eq!(
+            write_list_table(&table),
+            ".. list-table::\n   :header-rows: 1\n\n   * - a\n     - b\n   * - | one\n       | two\n     -\n"
+        );
+    }
+}
@@ -0,0 +1,718 @@
+//! Import of XLSX and ODS spreadsheets, available with the `sheet` feature.
+//!
+//! Cells are converted to the text a spreadsheet shows for common formats:
+//! dates as `2024-03-05`, times as `14:30`, durations as `36:00:00`, numbers
+//! rounded to the 15 significant digits a spreadsheet displays, and booleans
+//! as `TRUE` or `FALSE`. Percentages (`25%`) and currency (`$1,234.50`)
+//! follow the number format of their cell, read from the styles of XLSX files
+//! and from the text ODS files store for such cells.
+
+use crate::{Result, Table, TableError, WidthPolicy};
+use calamine::{open_workbook_auto, Data, ExcelDateTime, Reader};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Options selecting what part of a workbook to read and how to render it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SheetOptions {
+    /// Worksheet name, or the first worksheet when `None`.
+    pub sheet: Option<String>,
+
+    /// Cell range such as `A1:F20`, or the used area of the sheet when
+    /// `None`. The first row of the range holds the column names.
+    pub range: Option<String>,
+
+    /// How cell widths are measured when the sheet is rendered as Markdown.
+    pub width_policy: WidthPolicy,
+}
+
+/// How a cell's number format shows its value, for the formats that change
+/// what the value reads as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NumberFormat {
+    /// `0.0%`: the value times 100, with a fixed number of decimals.
+    Percent { decimals: usize },
+
+    /// `$#,##0.00` or `#,##0.00 €`: the value with a currency symbol.
+    Currency {
+        symbol: String,
+        prefix: bool,
+        decimals: usize,
+        grouping: bool,
+    },
+
+    /// The text an ODS file stores for the cell, as it was shown when saved.
+    Shown(String),
+}
+
+/// The number formats of the cells of a sheet, by zero-based (row, column).
+type CellFormats = HashMap<(u32, u32), NumberFormat>;
+
+/// Reads a worksheet from an XLSX, XLSM, XLSB, XLS or ODS file into a
+/// [`Table`].
+///
+/// The first row becomes the header and completely empty rows are skipped.
+///
+/// # Errors
+///
+/// Returns `TableError::InvalidStructure` if the file cannot be read, the
+/// sheet does not exist or the range is malformed, and
+/// `TableError::EmptyInput` if the selected cells are empty.
+pub fn read_table(path: &Path, options: &SheetOptions) -> Result<Table> {
+    let mut workbook = open_workbook_auto(path).map_err(|e| {
+        TableError::InvalidStructure(format!("Cannot read {}: {e}", path.display()))
+    })?;
+
+    let sheet_names = workbook.sheet_names();
+    let name = match &options.sheet {
+        Some(name) if sheet_names.contains(name) => name.clone(),
+        Some(name) => {
+            return Err(TableError::InvalidStructure(format!(
+                "Sheet {name:?} not found, available sheets: {}",
+                sheet_names.join(", ")
+            )))
+        }
+        None => sheet_names.first().cloned().ok_or(TableError::EmptyInput)?,
+    };
+
+    let mut cells = workbook
+        .worksheet_range(&name)
+        .map_err(|e| TableError::InvalidStructure(format!("Cannot read sheet {name:?}: {e}")))?;
+    if let Some(range) = &options.range {
+        let (start, end) = parse_range(range)?;
+        cells = cells.range(start, end);
+    }
+
+    // Formats are keyed by position in the sheet, and the range may not
+    // start at A1
+    let formats = read_formats(path, &name).unwrap_or_default();
+    let (start_row, start_col) = cells.start().unwrap_or_default();
+    let mut rows = (start_row..)
+        .zip(cells.rows())
+        .filter(|(_, row)| row.iter().any(|cell| *cell != Data::Empty))
+        .map(|(row_i, row)| {
+            (start_col..)
+                .zip(row)
+                .map(|(col_i, cell)| format_cell(cell, formats.get(&(row_i, col_i))))
+                .collect::<Vec<_>>()
+        });
+    let header = rows.next().ok_or(TableError::EmptyInput)?;
+
+    Ok(Table::new(header, rows.collect()))
+}
+
+/// Reads a worksheet and formats it as an aligned Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if the sheet cannot be read or formatted.
+pub fn to_markdown(path: &Path, options: &SheetOptions) -> Result<String> {
+    read_table(path, options)?.to_markdown_as(options.width_policy)
+}
+
+/// Converts a cell value into the text a spreadsheet would display.
+fn format_cell(cell: &Data, format: Option<&NumberFormat>) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(text) | Data::DateTimeIso(text) | Data::DurationIso(text) => text.clone(),
+        #[allow(clippy::cast_precision_loss)]
+        Data::Int(value) if format.is_some() => apply_format(*value as f64, format),
+        Data::Int(value) => value.to_string(),
+        Data::Float(value) => apply_format(*value, format),
+        Data::Bool(value) => if *value { "TRUE" } else { "FALSE" }.to_string(),
+        Data::DateTime(value) if value.is_duration() => format_duration(value.as_f64()),
+        Data::DateTime(value) => format_datetime(value),
+        Data::Error(error) => error.to_string(),
+    }
+}
+
+/// Formats a number as its cell's number format shows it.
+fn apply_format(value: f64, format: Option<&NumberFormat>) -> String {
+    match format {
+        None => format_number(value),
+        Some(NumberFormat::Percent { decimals }) => {
+            format!("{}%", format_fixed(value * 100.0, *decimals))
+        }
+        Some(NumberFormat::Currency {
+            symbol,
+            prefix,
+            decimals,
+            grouping,
+        }) => {
+            let digits = format_fixed(value.abs(), *decimals);
+            let digits = if *grouping {
+                group_thousands(&digits)
+            } else {
+                digits
+            };
+            // A value that rounds to zero has no sign
+            let sign = if value < 0.0 && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+                "-"
+            } else {
+                ""
+            };
+            if *prefix {
+                format!("{sign}{symbol}{digits}")
+            } else {
+                format!("{sign}{digits} {symbol}")
+            }
+        }
+        Some(NumberFormat::Shown(text)) => text.clone(),
+    }
+}
+
+/// Formats a number with a fixed number of decimals, rounding halves away
+/// from zero as spreadsheets do.
+fn format_fixed(value: f64, decimals: usize) -> String {
+    let scale = 10f64.powi(i32::try_from(decimals).unwrap_or(i32::MAX));
+    // Adding zero turns -0 into 0
+    let rounded = (value * scale).round() / scale + 0.0;
+    format!("{rounded:.decimals$}")
+}
+
+/// Inserts commas between groups of three digits: `1234567.5` becomes
+/// `1,234,567.5`.
+fn group_thousands(digits: &str) -> String {
+    let (integer, fraction) = digits.split_at(digits.find('.').unwrap_or(digits.len()));
+    let mut output = String::with_capacity(digits.len() + integer.len() / 3);
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            output.push(',');
+        }
+        output.push(c);
+    }
+    output.push_str(fraction);
+    output
+}
+
+/// Rounds to 15 significant digits, hiding binary floating point noise such
+/// as `0.30000000000000004`.
+fn format_number(value: f64) -> String {
+    let rounded: f64 = format!("{value:.14e}").parse().unwrap_or(value);
+    rounded.to_string()
+}
+
+/// Formats a date, a time of day, or both.
+fn format_datetime(value: &ExcelDateTime) -> String {
+    let (year, month, day, ..) = value.to_ymd_hms_milli();
+    // Round to the nearest second rather than truncating, staying on the same
+    // day so the date above remains valid
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let seconds = ((value.as_f64().fract() * 86_400.0).round() as u32).min(86_399);
+    let (hour, minute, second) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    let date = format!("{year:04}-{month:02}-{day:02}");
+    let time = if second == 0 {
+        format!("{hour:02}:{minute:02}")
+    } else {
+        format!("{hour:02}:{minute:02}:{second:02}")
+    };
+
+    if value.as_f64() < 1.0 {
+        time
+    } else if (hour, minute, second) == (0, 0, 0) {
+        date
+    } else {
+        format!("{date} {time}")
+    }
+}
+
+/// Formats a duration given in days as hours, minutes and seconds.
+fn format_duration(days: f64) -> String {
+    #[allow(clippy::cast_possible_truncation)]
+    let total_seconds = (days * 86_400.0).round() as i64;
+    let sign = if total_seconds < 0 { "-" } else { "" };
+    let total_seconds = total_seconds.abs();
+
+    format!(
+        "{sign}{}:{:02}:{:02}",
+        total_seconds / 3600,
+        total_seconds / 60 % 60,
+        total_seconds % 60
+    )
+}
+
+/// Parses an `A1:F20` range into zero-based (row, column) corners.
+fn parse_range(range: &str) -> Result<((u32, u32), (u32, u32))> {
+    let invalid = || {
+        TableError::InvalidStructure(format!(
+            "Invalid range {range:?}, expected cells such as A1:F20"
+        ))
+    };
+
+    let (start, end) = range.split_once(':').ok_or_else(invalid)?;
+    let start = parse_cell_reference(start).ok_or_else(invalid)?;
+    let end = parse_cell_reference(end).ok_or_else(invalid)?;
+    if start.0 > end.0 || start.1 > end.1 {
+        return Err(invalid());
+    }
+
+    Ok((start, end))
+}
+
+/// Parses a cell reference such as `B12` (or `$B$12`) into a zero-based
+/// (row, column) position.
+fn parse_cell_reference(reference: &str) -> Option<(u32, u32)> {
+    let reference = reference.trim().replace('$', "");
+    let digits = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, number) = reference.split_at(digits);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let column = letters.chars().try_fold(0u32, |column, c| {
+        let digit = c.to_ascii_uppercase() as u32 - 'A' as u32 + 1;
+        column.checked_mul(26)?.checked_add(digit)
+    })?;
+    let row: u32 = number.parse().ok()?;
+
+    Some((row.checked_sub(1)?, column - 1))
+}
+
+/// Reads the percentage and currency formats of the cells of a sheet, or
+/// `None` if the file has none that can be read.
+///
+/// XLS and XLSB files keep their styles in binary records and are read
+/// without formats.
+fn read_formats(path: &Path, sheet: &str) -> Option<CellFormats> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mut archive = ZipArchive::new(File::open(path).ok()?).ok()?;
+
+    match extension.as_str() {
+        "xlsx" | "xlsm" => {
+            let workbook = read_part(&mut archive, "xl/workbook.xml")?;
+            let id = find_attribute(&workbook, "sheet", ("name", sheet), "r:id")?;
+            let relationships = read_part(&mut archive, "xl/_rels/workbook.xml.rels")?;
+            let target = find_attribute(&relationships, "Relationship", ("Id", &id), "Target")?;
+            let sheet_part = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("xl/{target}"),
+            };
+
+            let styles = read_part(&mut archive, "xl/styles.xml")?;
+            let sheet_xml = read_part(&mut archive, &sheet_part)?;
+            Some(xlsx_cell_formats(&sheet_xml, &xlsx_style_formats(&styles)))
+        }
+        "ods" => Some(ods_cell_formats(
+            &read_part(&mut archive, "content.xml")?,
+            sheet,
+        )),
+        _ => None,
+    }
+}
+
+/// Reads a file of a zip archive as text.
+fn read_part(archive: &mut ZipArchive<File>, name: &str) -> Option<String> {
+    let mut text = String::new();
+    archive.by_name(name).ok()?.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+/// Returns the unescaped value of an attribute of an element.
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    let value = element.try_get_attribute(name).ok()??;
+    let value = value.decode_and_unescape_value(element.decoder()).ok()?;
+    Some(value.into_owned())
+}
+
+/// Finds the first `element` whose attribute `key.0` is `key.1` and returns
+/// its attribute `name`.
+fn find_attribute(xml: &str, element: &str, key: (&str, &str), name: &str) -> Option<String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e))
+                if e.name().as_ref() == element.as_bytes()
+                    && attribute(&e, key.0).as_deref() == Some(key.1) =>
+            {
+                return attribute(&e, name);
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Reads the number format of each cell style of an XLSX `styles.xml`, in
+/// style index order.
+fn xlsx_style_formats(styles: &str) -> Vec<Option<NumberFormat>> {
+    let mut codes = HashMap::new();
+    let mut format_ids = Vec::new();
+    let mut in_cell_styles = false;
+
+    let mut reader = quick_xml::Reader::from_str(styles);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e)) => match e.name().as_ref() {
+                b"numFmt" => {
+                    if let (Some(id), Some(code)) =
+                        (attribute(&e, "numFmtId"), attribute(&e, "formatCode"))
+                    {
+                        codes.insert(id, code);
+                    }
+                }
+                b"cellXfs" => in_cell_styles = true,
+                b"xf" if in_cell_styles => {
+                    format_ids.push(attribute(&e, "numFmtId").unwrap_or_default());
+                }
+                _ => {}
+            },
+            Ok(Event::End(e)) if e.name().as_ref() == b"cellXfs" => in_cell_styles = false,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    format_ids
+        .iter()
+        .map(|id| {
+            codes
+                .get(id)
+                .map(String::as_str)
+                .or_else(|| builtin_format_code(id))
+                .and_then(parse_format_code)
+        })
+        .collect()
+}
+
+/// Returns the code of a built-in XLSX number format that shows percentages
+/// or currency, in the en-US locale.
+fn builtin_format_code(id: &str) -> Option<&'static str> {
+    match id {
+        "5" | "6" => Some("$#,##0_);($#,##0)"),
+        "7" | "8" => Some("$#,##0.00_);($#,##0.00)"),
+        "9" => Some("0%"),
+        "10" => Some("0.00%"),
+        _ => None,
+    }
+}
+
+/// Finds the cells of an XLSX worksheet whose style shows a percentage or
+/// currency.
+fn xlsx_cell_formats(sheet: &str, styles: &[Option<NumberFormat>]) -> CellFormats {
+    let mut formats = CellFormats::new();
+
+    let mut reader = quick_xml::Reader::from_str(sheet);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e)) if e.name().as_ref() == b"c" => {
+                let position = attribute(&e, "r").and_then(|r| parse_cell_reference(&r));
+                let style = attribute(&e, "s").and_then(|s| s.parse::<usize>().ok());
+                if let (Some(position), Some(Some(format))) =
+                    (position, style.and_then(|style| styles.get(style)))
+                {
+                    formats.insert(position, format.clone());
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    formats
+}
+
+/// Reads what an Excel number format code shows: a percentage, a currency,
+/// or neither.
+///
+/// Only the first section, for positive numbers, is read. Negative numbers
+/// get a minus sign in front.
+fn parse_format_code(code: &str) -> Option<NumberFormat> {
+    let mut percent = false;
+    let mut symbol: Option<(String, bool)> = None;
+    let (mut decimals, mut grouping, mut in_fraction, mut seen_digit) = (0, false, false, false);
+    let mut chars = code.chars();
+
+    while let Some(c) = chars.next() {
+        let literal = match c {
+            ';' => break,
+            '"' => chars.by_ref().take_while(|&c| c != '"').collect(),
+            '\\' => chars.next().map(String::from).unwrap_or_default(),
+            // `[$€-407]` is a currency with a locale; colors and conditions
+            // such as `[Red]` are skipped
+            '[' => {
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                match inner.strip_prefix('$') {
+                    Some(currency) => currency.split('-').next().unwrap_or_default().to_string(),
+                    None => String::new(),
+                }
+            }
+            // Padding to the width of, or repeating, the next character
+            '_' | '*' => {
+                chars.next();
+                String::new()
+            }
+            '%' => {
+                percent = true;
+                String::new()
+            }
+            '.' => {
+                in_fraction = true;
+                String::new()
+            }
+            ',' => {
+                grouping |= seen_digit && !in_fraction;
+                String::new()
+            }
+            '0' | '#' | '?' => {
+                seen_digit = true;
+                if in_fraction {
+                    decimals += 1;
+                }
+                String::new()
+            }
+            c => c.to_string(),
+        };
+
+        let literal = literal.trim();
+        if symbol.is_none() && literal.chars().any(is_currency_symbol) {
+            symbol = Some((literal.to_string(), !seen_digit));
+        }
+    }
+
+    if percent {
+        Some(NumberFormat::Percent { decimals })
+    } else {
+        symbol.map(|(symbol, prefix)| NumberFormat::Currency {
+            symbol,
+            prefix,
+            decimals,
+            grouping,
+        })
+    }
+}
+
+/// Checks for a character that marks a currency in a number format.
+fn is_currency_symbol(c: char) -> bool {
+    matches!(
+        c,
+        '$' | '€' | '£' | '¥' | '₹' | '₽' | '₩' | '¢' | '₺' | '₪' | '₫'
+    )
+}
+
+/// Finds the percentage and currency cells of a sheet in an ODS
+/// `content.xml`, with the text stored for them.
+fn ods_cell_formats(content: &str, sheet: &str) -> CellFormats {
+    let mut formats = CellFormats::new();
+    let (mut in_sheet, mut row, mut col, mut rows_repeated) = (false, 0u32, 0u32, 1u32);
+    // The columns a formatted cell covers while its text is read
+    let mut formatted: Option<u32> = None;
+    let mut text = String::new();
+    let mut annotation_depth = 0;
+
+    let repeated = |e: &BytesStart, name: &str| {
+        attribute(e, name)
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(1u32)
+    };
+
+    let mut reader = quick_xml::Reader::from_str(content);
+    loop {
+        let event = reader.read_event();
+        match &event {
+            Ok(Event::Start(e) | Event::Empty(e)) if e.name().as_ref() == b"table:table" => {
+                in_sheet = attribute(e, "table:name").as_deref() == Some(sheet);
+                row = 0;
+            }
+            Ok(Event::End(e)) if in_sheet && e.name().as_ref() == b"table:table" => break,
+            _ if !in_sheet => {}
+            Ok(Event::Start(e) | Event::Empty(e)) if e.name().as_ref() == b"table:table-row" => {
+                rows_repeated = repeated(e, "table:number-rows-repeated");
+                col = 0;
+                if matches!(event, Ok(Event::Empty(_))) {
+                    row = row.saturating_add(rows_repeated);
+                }
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"table:table-row" => {
+                row = row.saturating_add(rows_repeated);
+            }
+            Ok(Event::Start(e) | Event::Empty(e))
+                if matches!(
+                    e.name().as_ref(),
+                    b"table:table-cell" | b"table:covered-table-cell"
+                ) =>
+            {
+                let columns = repeated(e, "table:number-columns-repeated");
+                let value_type = attribute(e, "office:value-type");
+                if matches!(event, Ok(Event::Start(_)))
+                    && matches!(value_type.as_deref(), Some("percentage" | "currency"))
+                {
+                    formatted = Some(columns);
+                    text.clear();
+                } else {
+                    col = col.saturating_add(columns);
+                }
+            }
+            Ok(Event::End(e))
+                if matches!(
+                    e.name().as_ref(),
+                    b"table:table-cell" | b"table:covered-table-cell"
+                ) =>
+            {
+                if let Some(columns) = formatted.take() {
+                    for row_i in row..row.saturating_add(rows_repeated) {
+                        for col_i in col..col.saturating_add(columns) {
+                            formats.insert((row_i, col_i), NumberFormat::Shown(text.trim().into()));
+                        }
+                    }
+                    col = col.saturating_add(columns);
+                }
+            }
+            _ if formatted.is_none() => {}
+            Ok(Event::Start(e)) if e.name().as_ref() == b"office:annotation" => {
+                annotation_depth += 1;
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"office:annotation" => {
+                annotation_depth -= 1;
+            }
+            _ if annotation_depth > 0 => {}
+            Ok(Event::Text(t)) => text.push_str(&t.decode().unwrap_or_default()),
+            Ok(Event::GeneralRef(r)) => {
+                let name = r.decode().unwrap_or_default();
+                if let Ok(Some(c)) = r.resolve_char_ref() {
+                    text.push(c);
+                } else if let Some(entity) = quick_xml::escape::resolve_predefined_entity(&name) {
+                    text.push_str(entity);
+                }
+            }
+            Ok(Event::Start(e) | Event::Empty(e)) if e.name().as_ref() == b"text:s" => {
+                let spaces = repeated(e, "text:c");
+                text.extend(std::iter::repeat_n(' ', spaces as usize));
+            }
+            _ => {}
+        }
+        if matches!(event, Ok(Event::Eof) | Err(_)) {
+            break;
+        }
+    }
+
+    formats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::ExcelDateTimeType;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("A1:F20").unwrap(), ((0, 0), (19, 5)));
+        assert_eq!(parse_range("$AA$2:ab3").unwrap(), ((1, 26), (2, 27)));
+        assert!(parse_range("A0:B2").is_err());
+        assert!(parse_range("C3:A1").is_err());
+        assert!(parse_range("A1").is_err());
+    }
+
+    #[test]
+    fn test_format_numbers_and_booleans() {
+        assert_eq!(format_cell(&Data::Float(0.1 + 0.2), None), "0.3");
+        assert_eq!(format_cell(&Data::Float(1234.5), None), "1234.5");
+        assert_eq!(format_cell(&Data::Float(3.0), None), "3");
+        assert_eq!(format_cell(&Data::Bool(true), None), "TRUE");
+    }
+
+    #[test]
+    fn test_format_dates_times_and_durations() {
+        let datetime = |value| {
+            Data::DateTime(ExcelDateTime::new(
+                value,
+                ExcelDateTimeType::DateTime,
+                false,
+            ))
+        };
+
+        assert_eq!(format_cell(&datetime(45356.0), None), "2024-03-05");
+        assert_eq!(
+            format_cell(&datetime(45356.0 + 14.5 / 24.0), None),
+            "2024-03-05 14:30"
+        );
+        assert_eq!(format_cell(&datetime(0.5), None), "12:00");
+        assert_eq!(
+            format_cell(
+                &Data::DateTime(ExcelDateTime::new(1.5, ExcelDateTimeType::TimeDelta, false)),
+                None
+            ),
+            "36:00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_format_codes() {
+        assert_eq!(
+            parse_format_code("0.0%"),
+            Some(NumberFormat::Percent { decimals: 1 })
+        );
+        assert_eq!(
+            parse_format_code("$#,##0.00_);[Red]($#,##0.00)"),
+            Some(NumberFormat::Currency {
+                symbol: "$".to_string(),
+                prefix: true,
+                decimals: 2,
+                grouping: true,
+            })
+        );
+        assert_eq!(
+            parse_format_code("#,##0\\ [$€-407]"),
+            Some(NumberFormat::Currency {
+                symbol: "€".to_string(),
+                prefix: false,
+                decimals: 0,
+                grouping: true,
+            })
+        );
+        assert_eq!(parse_format_code("[$-409]0.00"), None);
+        assert_eq!(parse_format_code("\"Y: \"0.00"), None);
+    }
+
+    #[test]
+    fn test_format_percent_and_currency() {
+        let percent = NumberFormat::Percent { decimals: 0 };
+        assert_eq!(format_cell(&Data::Float(0.125), Some(&percent)), "13%");
+        assert_eq!(format_cell(&Data::Int(1), Some(&percent)), "100%");
+
+        let pounds = NumberFormat::Currency {
+            symbol: "£".to_string(),
+            prefix: true,
+            decimals: 2,
+            grouping: true,
+        };
+        assert_eq!(
+            format_cell(&Data::Float(-1_234_567.891), Some(&pounds)),
+            "-£1,234,567.89"
+        );
+        assert_eq!(format_cell(&Data::Float(-0.001), Some(&pounds)), "£0.00");
+    }
+
+    #[test]
+    fn test_ods_cell_formats() {
+        let content = r#"<office:document-content><office:body><office:spreadsheet>
+            <table:table table:name="Other"><table:table-row>
+              <table:table-cell office:value-type="percentage" office:value="1"><text:p>100%</text:p></table:table-cell>
+            </table:table-row></table:table>
+            <table:table table:name="Budget">
+              <table:table-row table:number-rows-repeated="2"><table:table-cell/></table:table-row>
+              <table:table-row>
+                <table:table-cell table:number-columns-repeated="2"/>
+                <table:table-cell office:value-type="percentage" office:value="0.25"><text:p>25.0%</text:p></table:table-cell>
+                <table:table-cell office:value-type="float" office:value="3"><text:p>3</text:p></table:table-cell>
+                <table:table-cell office:value-type="currency" office:currency="EUR" office:value="1234.5"><text:p>1.234,50<text:s/>&#8364;</text:p></table:table-cell>
+              </table:table-row>
+            </table:table>
+        </office:spreadsheet></office:body></office:document-content>"#;
+
+        let formats = ods_cell_formats(content, "Budget");
+        assert_eq!(formats.len(), 2);
+        assert_eq!(
+            formats.get(&(2, 2)),
+            Some(&NumberFormat::Shown("25.0%".to_string()))
+        );
+        assert_eq!(
+            formats.get(&(2, 4)),
+            Some(&NumberFormat::Shown("1.234,50 €".to_string()))
+        );
+    }
+}
@@ -0,0 +1,397 @@
+//! Result tables printed by database command-line clients.
+//!
+//! Supported layouts are `psql` aligned output, bordered output from the
+//! MySQL client (and `psql` with `\pset border 2`), and sqlite's
+//! `.mode box`, `.mode table` and `.mode column`:
+//!
+//! ```text
+//!  id | name           +----+------+     ┌────┬──────┐     id  name
+//! ----+------          | id | name |     │ id │ name │     --  ----
+//!   1 | tea            +----+------+     ├────┼──────┤     1   tea
+//! (1 row)              |  1 | tea  |     │ 1  │ tea  │
+//!                      +----+------+     └────┴──────┘
+//! ```
+
+use crate::rst::{border_runs, split_simple_line};
+use crate::{Result, Table, TableError};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Parses the first query result table in the input.
+///
+/// Text before the table, such as the prompt and query, is skipped, and the
+/// table ends at a blank line or a row count footer (`(2 rows)`,
+/// `2 rows in set`). Multi-line `psql` values, whose lines end with a `+`
+/// continuation marker, are joined with line breaks. Bordered tables without
+/// a header separator get an empty header.
+///
+/// # Errors
+///
+/// Returns `TableError::InvalidStructure` if no result table is found or a
+/// row does not line up with the header.
+///
+/// # Examples
+///
+/// ```
+/// let input = "app=> select id, name from fruit;\n id | name\n----+--------\n  1 | apple\n  2 | kiwi\n(2 rows)\n";
+/// let table = ftb::sql::parse_table(input).unwrap();
+/// assert_eq!(table.header, vec!["id", "name"]);
+/// assert_eq!(table.rows, vec![vec!["1", "apple"], vec!["2", "kiwi"]]);
+/// ```
+pub fn parse_table(input: &str) -> Result<Table> {
+    let lines: Vec<&str> = input.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let follows_text = i > 0 && !lines[i - 1].trim().is_empty();
+
+        if let Some(frame) = Frame::of_top_border(line.trim()) {
+            return parse_bordered(&lines, i, frame);
+        }
+        if follows_text && is_psql_separator(line) {
+            return parse_psql(&lines, i);
+        }
+        if follows_text && is_column_separator(line) {
+            return parse_columns(&lines, i);
+        }
+    }
+
+    Err(TableError::InvalidStructure(
+        "No psql, MySQL or sqlite result table found".to_string(),
+    ))
+}
+
+/// Converts a query result table into an aligned Markdown table.
+///
+/// # Errors
+///
+/// Returns `TableError` if the input cannot be parsed or formatted.
+///
+/// # Examples
+///
+/// ```
+/// let input = "+----+-------+\n| id | name  |\n+----+-------+\n|  1 | apple |\n+----+-------+\n1 row in set (0.00 sec)\n";
+/// assert_eq!(
+///     ftb::sql::to_markdown(input).unwrap(),
+///     "| id | name  |\n|----|-------|\n| 1  | apple |\n"
+/// );
+/// ```
+pub fn to_markdown(input: &str) -> Result<String> {
+    parse_table(input)?.to_markdown()
+}
+
+/// The characters a bordered table is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    /// `+----+` borders and `|` separators
+    Ascii,
+    /// `┌────┐` borders and `│` separators
+    Unicode,
+}
+
+impl Frame {
+    /// Recognizes the top border of a bordered table.
+    fn of_top_border(line: &str) -> Option<Self> {
+        match line.chars().next() {
+            Some('+') | Some('┌') => Self::of_border(line),
+            _ => None,
+        }
+    }
+
+    /// Recognizes any border line of a bordered table.
+    fn of_border(line: &str) -> Option<Self> {
+        let frame = if line.starts_with('+') {
+            Self::Ascii
+        } else {
+            Self::Unicode
+        };
+        let valid = line.chars().count() >= 3
+            && frame.is_junction(line.chars().next()?)
+            && frame.is_junction(line.chars().last()?)
+            && line
+                .chars()
+                .all(|c| c == frame.fill() || frame.is_junction(c))
+            && line.contains(frame.fill());
+
+        valid.then_some(frame)
+    }
+
+    fn fill(self) -> char {
+        match self {
+            Self::Ascii => '-',
+            Self::Unicode => '─',
+        }
+    }
+
+    fn separator(self) -> char {
+        match self {
+            Self::Ascii => '|',
+            Self::Unicode => '│',
+        }
+    }
+
+    fn is_junction(self, c: char) -> bool {
+        match self {
+            Self::Ascii => c == '+',
+            Self::Unicode => "┌┬┐├┼┤└┴┘".contains(c),
+        }
+    }
+}
+
+/// Parses a table framed by borders, starting at its top border.
+fn parse_bordered(lines: &[&str], start: usize, frame: Frame) -> Result<Table> {
+    let top = lines[start].trim();
+    let boundaries = display_columns(top, |c| frame.is_junction(c));
+    let num_columns = boundaries.len() - 1;
+
+    // Rows grouped by the borders between them
+    let mut blocks: Vec<Vec<Vec<String>>> = vec![Vec::new()];
+    for (offset, line) in lines[start + 1..].iter().enumerate() {
+        let content = line.trim();
+        if Frame::of_border(content) == Some(frame) {
+            blocks.push(Vec::new());
+            continue;
+        }
+        if !content.starts_with(frame.separator()) {
+            break;
+        }
+
+        let segments = split_row(content, &boundaries, frame.separator())
+            .ok_or_else(|| misaligned_row(start + offset + 2))?;
+        let row = segments[1..=num_columns]
+            .iter()
+            .map(|cell| cell.trim().to_string())
+            .collect();
+        if let Some(block) = blocks.last_mut() {
+            block.push(row);
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+
+    if blocks.len() >= 2 && blocks[0].len() == 1 {
+        let header = blocks.remove(0).remove(0);
+        return Ok(Table::new(header, blocks.concat()));
+    }
+    Ok(Table::new(
+        vec![String::new(); num_columns],
+        blocks.concat(),
+    ))
+}
+
+/// Parses `psql` aligned output whose `----+----` separator is at `separator`.
+fn parse_psql(lines: &[&str], separator: usize) -> Result<Table> {
+    let rule = lines[separator].trim_end();
+    let boundaries = display_columns(rule, |c| c == '+');
+    let widths: Vec<usize> = rule.split('+').map(str::len).collect();
+
+    let header = split_row(lines[separator - 1], &boundaries, '|')
+        .ok_or_else(|| misaligned_row(separator))?
+        .iter()
+        .map(|cell| cell.trim().to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    // A row whose values continue on the next line, and which columns do
+    let mut open: Option<(Vec<String>, Vec<bool>)> = None;
+
+    for (offset, line) in lines[separator + 1..].iter().enumerate() {
+        if line.trim().is_empty() || is_row_count(line) {
+            break;
+        }
+
+        let segments = split_row(line, &boundaries, '|')
+            .ok_or_else(|| misaligned_row(separator + offset + 2))?;
+        let mut continued = vec![false; segments.len()];
+        let texts: Vec<String> = segments
+            .iter()
+            .enumerate()
+            .map(|(col_i, segment)| {
+                // The marker sits in the last display column of the segment
+                let segment = segment.trim_end();
+                match segment.strip_suffix('+') {
+                    Some(text) if segment.width() == widths[col_i] => {
+                        continued[col_i] = true;
+                        text.trim().to_string()
+                    }
+                    _ => segment.trim().to_string(),
+                }
+            })
+            .collect();
+
+        let row = match open.take() {
+            Some((mut row, was_continued)) => {
+                for (col_i, text) in texts.into_iter().enumerate() {
+                    if was_continued[col_i] {
+                        row[col_i].push('\n');
+                        row[col_i].push_str(&text);
+                    }
+                }
+                row
+            }
+            None => texts,
+        };
+
+        if continued.contains(&true) {
+            open = Some((row, continued));
+        } else {
+            rows.push(row);
+        }
+    }
+    rows.extend(open.map(|(row, _)| row));
+
+    Ok(Table::new(header, rows))
+}
+
+/// Parses sqlite `.mode column` output whose dash rule is at `separator`.
+fn parse_columns(lines: &[&str], separator: usize) -> Result<Table> {
+    let columns = border_runs(lines[separator].trim_end(), '-');
+    let header = split_simple_line(lines[separator - 1], &columns)
+        .ok_or_else(|| misaligned_row(separator))?;
+
+    let mut rows = Vec::new();
+    for (offset, line) in lines[separator + 1..].iter().enumerate() {
+        if line.trim().is_empty() {
+            break;
+        }
+        let row = split_simple_line(line, &columns)
+            .ok_or_else(|| misaligned_row(separator + offset + 2))?;
+        rows.push(row);
+    }
+
+    Ok(Table::new(header, rows))
+}
+
+/// Cuts a row at the display columns of the separators, keeping the text
+/// before the first and after the last. Lines that end early get empty
+/// trailing segments. If values have shifted the layout, the row is split on
+/// the separator instead, provided that yields the same number of segments.
+fn split_row(line: &str, boundaries: &[usize], separator: char) -> Option<Vec<String>> {
+    let mut segments = vec![String::new(); boundaries.len() + 1];
+    let mut column = 0;
+    let mut next = 0;
+    let mut aligned = true;
+
+    for c in line.chars() {
+        if next < boundaries.len() && column >= boundaries[next] {
+            if column > boundaries[next] || c != separator {
+                aligned = false;
+                break;
+            }
+            next += 1;
+        } else {
+            segments[next].push(c);
+        }
+        column += c.width().unwrap_or(0);
+    }
+    if aligned {
+        return Some(segments);
+    }
+
+    let segments: Vec<String> = line.split(separator).map(str::to_string).collect();
+    (segments.len() == boundaries.len() + 1).then_some(segments)
+}
+
+/// Display columns of the characters in `line` that match `is_boundary`.
+fn display_columns(line: &str, is_boundary: impl Fn(char) -> bool) -> Vec<usize> {
+    let mut columns = Vec::new();
+    let mut column = 0;
+
+    for c in line.chars() {
+        if is_boundary(c) {
+            columns.push(column);
+        }
+        column += c.width().unwrap_or(0);
+    }
+
+    columns
+}
+
+/// Checks for a `psql` header separator such as `----+------`.
+fn is_psql_separator(line: &str) -> bool {
+    let line = line.trim_end();
+    line.starts_with('-') && line.ends_with('-') && line.chars().all(|c| c == '-' || c == '+')
+}
+
+/// Checks for a sqlite `.mode column` rule such as `--  ----`.
+fn is_column_separator(line: &str) -> bool {
+    let line = line.trim_end();
+    line.starts_with('-') && line.chars().all(|c| c == '-' || c == ' ')
+}
+
+/// Checks for the `psql` row count footer, e.g. `(3 rows)`.
+fn is_row_count(line: &str) -> bool {
+    line.trim()
+        .strip_prefix('(')
+        .and_then(|rest| {
+            rest.strip_suffix(" rows)")
+                .or_else(|| rest.strip_suffix(" row)"))
+        })
+        .is_some_and(|count| !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn misaligned_row(line_number: usize) -> TableError {
+    TableError::InvalidStructure(format!(
+        "Row on line {line_number} does not line up with the table columns"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psql_multi_line_values() {
+        let input = "\
+ id |  note  | tag
+----+--------+-----
+  1 | line1 +| a
+    | line2  |
+  2 | x | y  | b
+(2 rows)";
+        let table = parse_table(input).expect("Should parse");
+
+        assert_eq!(table.header, vec!["id", "note", "tag"]);
+        assert_eq!(
+            table.rows,
+            vec![vec!["1", "line1\nline2", "a"], vec!["2", "x | y", "b"]]
+        );
+    }
+
+    #[test]
+    fn test_sqlite_box_with_wide_characters() {
+        let input = "\
+┌────┬──────┐
+│ id │ name │
+├────┼──────┤
+│ 1  │ 茶   │
+│ 2  │ a│b  │
+└────┴──────┘";
+        let table = parse_table(input).expect("Should parse");
+
+        assert_eq!(table.header, vec!["id", "name"]);
+        assert_eq!(table.rows, vec![vec!["1", "茶"], vec!["2", "a│b"]]);
+    }
+
+    #[test]
+    fn test_sqlite_column_mode() {
+        let input = "sqlite> select * from t;\nid  name        \n--  ------------\n1   green tea   \n2   kiwi        \n";
+        let table = parse_table(input).expect("Should parse");
+
+        assert_eq!(table.header, vec!["id", "name"]);
+        assert_eq!(table.rows, vec![vec!["1", "green tea"], vec!["2", "kiwi"]]);
+    }
+
+    #[test]
+    fn test_bordered_without_header_separator() {
+        let table = parse_table("+---+\n| a |\n| b |\n+---+").expect("Should parse");
+
+        assert_eq!(table.header, vec![""]);
+        assert_eq!(table.rows, vec![vec!["a"], vec!["b"]]);
+    }
+
+    #[test]
+    fn test_no_table_error() {
+        assert!(matches!(
+            parse_table("ERROR:  relation \"t\" does not exist"),
+            Err(TableError::InvalidStructure(_))
+        ));
+    }
+}
@@ -0,0 +1,568 @@
+use crate::{Result, TableFormatter, WidthPolicy};
+
+/// The kind of values found in a column, inferred from its non-empty cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Every value is an integer, e.g. `42` or `-7`.
+    Integer,
+
+    /// Every value is a number and at least one is not an integer, e.g. `1.5`.
+    Number,
+
+    /// Every value is `true` or `false` (case-insensitive).
+    Boolean,
+
+    /// Anything else, including columns with no values at all.
+    Text,
+}
+
+impl ColumnType {
+    /// Infers the type shared by all non-empty values.
+    fn infer<'a>(values: impl Iterator<Item = &'a str>) -> Self {
+        let mut column_type = None;
+
+        for value in values.map(str::trim).filter(|value| !value.is_empty()) {
+            let value_type = if value.parse::<i64>().is_ok() {
+                ColumnType::Integer
+            } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+                ColumnType::Number
+            } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+                ColumnType::Boolean
+            } else {
+                return ColumnType::Text;
+            };
+
+            column_type = match (column_type, value_type) {
+                (None, value_type) => Some(value_type),
+                (Some(a), b) if a == b => Some(a),
+                (
+                    Some(ColumnType::Integer | ColumnType::Number),
+                    ColumnType::Integer | ColumnType::Number,
+                ) => Some(ColumnType::Number),
+                _ => return ColumnType::Text,
+            };
+        }
+
+        column_type.unwrap_or(ColumnType::Text)
+    }
+}
+
+/// Horizontal alignment of a column, as declared by the separator row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// No alignment given (`---`).
+    #[default]
+    None,
+
+    /// Left aligned (`:--`).
+    Left,
+
+    /// Centered (`:-:`).
+    Center,
+
+    /// Right aligned (`--:`).
+    Right,
+}
+
+impl Alignment {
+    /// Parses a separator cell such as `---`, `:--`, `:-:` or `--:`.
+    ///
+    /// Returns `None` if the cell is not a valid separator cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::Alignment;
+    ///
+    /// assert_eq!(Alignment::from_separator(":---:"), Some(Alignment::Center));
+    /// assert_eq!(Alignment::from_separator("abc"), None);
+    /// ```
+    #[must_use]
+    pub fn from_separator(cell: &str) -> Option<Self> {
+        let cell = cell.trim();
+        let left = cell.starts_with(':');
+        let right = cell.len() > 1 && cell.ends_with(':');
+        let dashes = &cell[usize::from(left)..cell.len() - usize::from(right)];
+
+        if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+            return None;
+        }
+
+        Some(match (left, right) {
+            (false, false) => Alignment::None,
+            (true, false) => Alignment::Left,
+            (true, true) => Alignment::Center,
+            (false, true) => Alignment::Right,
+        })
+    }
+
+    /// Returns the shortest separator cell with this alignment.
+    #[must_use]
+    pub fn separator_cell(self) -> &'static str {
+        match self {
+            Alignment::None => "-",
+            Alignment::Left => ":-",
+            Alignment::Center => ":-:",
+            Alignment::Right => "-:",
+        }
+    }
+
+    /// Whether the separator carries a colon on the left.
+    #[must_use]
+    pub fn is_left(self) -> bool {
+        matches!(self, Alignment::Left | Alignment::Center)
+    }
+
+    /// Whether the separator carries a colon on the right.
+    #[must_use]
+    pub fn is_right(self) -> bool {
+        matches!(self, Alignment::Right | Alignment::Center)
+    }
+}
+
+/// A table held as plain cell text, independent of any markup.
+///
+/// Cell values are stored unescaped: a literal `|` is kept as `|` and only
+/// escaped when the table is rendered back to Markdown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    /// Header cells.
+    pub header: Vec<String>,
+
+    /// Body rows. Rows may be shorter or longer than the header.
+    pub rows: Vec<Vec<String>>,
+
+    /// Column alignments. Columns without an entry have [`Alignment::None`].
+    pub alignments: Vec<Alignment>,
+}
+
+impl Table {
+    /// Creates a table from a header row and body rows.
+    #[must_use]
+    pub fn new(header: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        Self {
+            header,
+            rows,
+            alignments: Vec::new(),
+        }
+    }
+
+    /// Returns the alignment of a column.
+    #[must_use]
+    pub fn alignment(&self, col_i: usize) -> Alignment {
+        self.alignments.get(col_i).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of columns, taking the widest row into account.
+    #[must_use]
+    pub fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.header.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Infers the [`ColumnType`] of each column from its body cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{ColumnType, Table};
+    ///
+    /// let table = Table::new(
+    ///     vec!["id".to_string(), "price".to_string(), "name".to_string()],
+    ///     vec![
+    ///         vec!["1".to_string(), "9.5".to_string(), "tea".to_string()],
+    ///         vec!["2".to_string(), "3".to_string(), "".to_string()],
+    ///     ],
+    /// );
+    /// assert_eq!(
+    ///     table.column_types(),
+    ///     vec![ColumnType::Integer, ColumnType::Number, ColumnType::Text]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn column_types(&self) -> Vec<ColumnType> {
+        (0..self.column_count())
+            .map(|col_i| {
+                ColumnType::infer(
+                    self.rows
+                        .iter()
+                        .filter_map(|row| row.get(col_i).map(String::as_str)),
+                )
+            })
+            .collect()
+    }
+
+    /// Renders the table as an aligned Markdown pipe table.
+    ///
+    /// Pipes and other characters Markdown reads as markup are escaped with a
+    /// backslash (code spans, links and `<br>` tags are kept) and embedded
+    /// newlines are converted to `<br>`, then the result is run through
+    /// [`TableFormatter`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `TableError` if the formatter rejects the table, e.g. when it
+    /// exceeds the maximum size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::Table;
+    ///
+    /// let table = Table::new(
+    ///     vec!["name".to_string(), "value".to_string()],
+    ///     vec![vec!["a|b".to_string(), "1".to_string()]],
+    /// );
+    /// let output = table.to_markdown().unwrap();
+    /// assert_eq!(output, "| name | value |\n|------|-------|\n| a\\|b | 1     |\n");
+    /// ```
+    pub fn to_markdown(&self) -> Result<String> {
+        self.to_markdown_as(WidthPolicy::default())
+    }
+
+    /// Renders the table like [`Table::to_markdown`], measuring cell widths
+    /// with the given [`WidthPolicy`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `TableError` if the formatter rejects the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ftb::{Table, WidthPolicy};
+    ///
+    /// let table = Table::new(vec!["①".to_string()], vec![vec!["a".to_string()]]);
+    /// let output = table.to_markdown_as(WidthPolicy::Cjk).unwrap();
+    /// assert_eq!(output, "| ① |\n|----|\n| a  |\n");
+    /// ```
+    pub fn to_markdown_as(&self, width_policy: WidthPolicy) -> Result<String> {
+        let num_columns = self.column_count().max(1);
+        let mut text = String::new();
+
+        push_markdown_row(&mut text, &self.header);
+        text.push('|');
+        for col_i in 0..num_columns {
+            text.push_str(self.alignment(col_i).separator_cell());
+            text.push('|');
+        }
+        text.push('\n');
+        for row in &self.rows {
+            push_markdown_row(&mut text, row);
+        }
+
+        TableFormatter::new()
+            .with_width_policy(width_policy)
+            .format_table(&text)
+    }
+}
+
+/// Appends one pipe-delimited row, escaping cell content for Markdown.
+fn push_markdown_row(text: &mut String, row: &[String]) {
+    text.push('|');
+    for cell in row {
+        text.push(' ');
+        text.push_str(&escape_markdown_cell(cell));
+        text.push_str(" |");
+    }
+    text.push('\n');
+}
+
+/// Characters that are backslash-escaped in Markdown cells so they read literally.
+pub(crate) const MARKDOWN_ESCAPED: [char; 9] = ['\\', '|', '*', '_', '`', '[', ']', '<', '>'];
+
+/// Escapes a plain cell value so it fits on a single Markdown table line.
+///
+/// Characters Markdown reads as markup are backslash-escaped, and backslashes
+/// right before one of them are doubled, so `a\|b` stays one cell rather than
+/// ending in an escaped backslash followed by a real pipe. Code spans, links
+/// and `<br>` line breaks are kept as markup.
+pub(crate) fn escape_markdown_cell(cell: &str) -> String {
+    let cell = cell.trim().replace("\r\n", "\n");
+    let mut output = String::with_capacity(cell.len());
+    push_escaped(&mut output, &cell);
+    output
+}
+
+fn push_escaped(output: &mut String, text: &str) {
+    let mut rest = text;
+    let mut backslashes = 0;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' {
+            backslashes += 1;
+            output.push(c);
+            rest = &rest[1..];
+            continue;
+        }
+        if MARKDOWN_ESCAPED.contains(&c) {
+            output.extend(std::iter::repeat_n('\\', backslashes));
+        }
+        backslashes = 0;
+
+        if let Some(len) = line_break_len(rest) {
+            output.push_str(&rest[..len]);
+            rest = &rest[len..];
+        } else if let Some(len) = code_span_len(rest) {
+            push_code_span(output, &rest[..len]);
+            rest = &rest[len..];
+        } else if let Some((label, url, len)) = link(rest) {
+            output.push('[');
+            push_escaped(output, label);
+            output.push_str("](");
+            push_code_span(output, url);
+            output.push(')');
+            rest = &rest[len..];
+        } else {
+            match c {
+                '\n' => output.push_str("<br>"),
+                c if MARKDOWN_ESCAPED.contains(&c) => {
+                    output.push('\\');
+                    output.push(c);
+                }
+                _ => output.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+}
+
+/// Copies markup whose characters are literal, escaping only what a table
+/// row needs: pipes and line breaks.
+fn push_code_span(output: &mut String, span: &str) {
+    let mut backslashes = 0;
+
+    for c in span.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                output.push(c);
+                continue;
+            }
+            '|' => {
+                output.extend(std::iter::repeat_n('\\', backslashes));
+                output.push_str("\\|");
+            }
+            '\n' => output.push_str("<br>"),
+            _ => output.push(c),
+        }
+        backslashes = 0;
+    }
+}
+
+/// Returns the length of a code span such as `` `x` `` at the start of `text`.
+pub(crate) fn code_span_len(text: &str) -> Option<usize> {
+    let run = text.len() - text.trim_start_matches('`').len();
+    if run == 0 {
+        return None;
+    }
+
+    let mut offset = run;
+    while let Some(start) = text[offset..].find('`') {
+        let start = offset + start;
+        let end = start + (text.len() - start - text[start..].trim_start_matches('`').len());
+        if end - start == run {
+            return Some(end);
+        }
+        offset = end;
+    }
+
+    None
+}
+
+/// Splits a `[label](url)` link at the start of `text` into its label, its
+/// URL and its total length.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let rest = text.strip_prefix('[')?;
+    let label_end = rest.find([']', '[', '\n'])?;
+    let url_start = label_end + 2;
+    if !rest[label_end..].starts_with("](") {
+        return None;
+    }
+    let url_end = url_start + rest[url_start..].find([')', ' ', '\n'])?;
+    if !rest[url_end..].starts_with(')') {
+        return None;
+    }
+
+    Some((&rest[..label_end], &rest[url_start..url_end], url_end + 2))
+}
+
+/// Returns the length of a `<br>`, `<br/>` or `<br />` tag at the start of `text`.
+pub(crate) fn line_break_len(text: &str) -> Option<usize> {
+    ["<br>", "<br/>", "<br />"]
+        .iter()
+        .find(|tag| {
+            text.get(..tag.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(tag))
+        })
+        .map(|tag| tag.len())
+}
+
+/// Splits cell text into lines at line breaks and `<br>` tags.
+///
+/// Trailing whitespace is removed from every line. Indentation after a line
+/// break is kept (it may belong to a nested list), while spaces around a
+/// `<br>` tag are dropped.
+pub(crate) fn cell_lines(cell: &str) -> Vec<&str> {
+    let cell = cell.trim();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < cell.len() {
+        let rest = &cell[i..];
+        if rest.starts_with('\n') {
+            lines.push(cell[start..i].trim_end());
+            i += 1;
+            start = i;
+        } else if let Some(len) = line_break_len(rest) {
+            lines.push(cell[start..i].trim_end());
+            i += len;
+            i += cell[i..].len() - cell[i..].trim_start_matches(' ').len();
+            start = i;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    lines.push(&cell[start..]);
+
+    lines
+}
+
+/// Pads text with spaces to a display width, honoring column alignment.
+///
+/// Columns without an explicit alignment are padded on the right.
+pub(crate) fn pad_cell(text: &str, width: usize, alignment: Alignment) -> String {
+    pad_cell_as(text, width, alignment, WidthPolicy::Standard)
+}
+
+/// Like [`pad_cell`], measuring text with the given width policy.
+pub(crate) fn pad_cell_as(
+    text: &str,
+    width: usize,
+    alignment: Alignment,
+    width_policy: WidthPolicy,
+) -> String {
+    let padding = width.saturating_sub(width_policy.width(text));
+    let (left, right) = match alignment {
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::None | Alignment::Left => (0, padding),
+    };
+
+    let mut output = String::with_capacity(text.len() + padding);
+    output.extend(std::iter::repeat_n(' ', left));
+    output.push_str(text);
+    output.extend(std::iter::repeat_n(' ', right));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| (*cell).to_string()).collect()
+    }
+
+    #[test]
+    fn test_to_markdown_escapes_cells() {
+        let table = Table::new(
+            strings(&["a", "b"]),
+            vec![strings(&["x|y", "line 1\nline 2"])],
+        );
+        let output = table.to_markdown().expect("Should render");
+
+        assert_eq!(
+            output,
+            "| a    | b                |\n|------|------------------|\n| x\\|y | line 1<br>line 2 |\n"
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_escapes_markup_characters() {
+        let table = Table::new(
+            strings(&["a"]),
+            vec![
+                strings(&["1 < 2 *x* a\\b_c"]),
+                strings(&["`a*b` [x_y](/u_v) [z]"]),
+            ],
+        );
+        let output = table.to_markdown().expect("Should render");
+
+        assert_eq!(
+            output,
+            "| a                        |\n|--------------------------|\n| 1 \\< 2 \\*x\\* a\\b\\_c      |\n| `a*b` [x\\_y](/u_v) \\[z\\] |\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_escaping_round_trips() {
+        let cells = strings(&[
+            "a\\|b",
+            "c\\\\|d",
+            "e|f",
+            "g\\h",
+            "*x* \\_y_ <z>",
+            "`a|*b*` [l*](/u_v) [w]",
+        ]);
+        let table = Table::new(cells.clone(), vec![cells.clone()]);
+        let output = table.to_markdown().expect("Should render");
+
+        let parsed = TableFormatter::new()
+            .parse_table(&output)
+            .expect("Should parse");
+        assert_eq!(parsed.header, cells);
+        assert_eq!(parsed.rows, vec![cells]);
+    }
+
+    #[test]
+    fn test_column_types_ignore_empty_cells() {
+        let table = Table::new(
+            strings(&["a", "b", "c", "d"]),
+            vec![
+                strings(&["1", "TRUE", "", "1"]),
+                strings(&["", "false", "", "x"]),
+            ],
+        );
+
+        assert_eq!(
+            table.column_types(),
+            vec![
+                ColumnType::Integer,
+                ColumnType::Boolean,
+                ColumnType::Text,
+                ColumnType::Text
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_lines_split_on_breaks() {
+        assert_eq!(cell_lines("a<br>b <BR/> c\nd"), vec!["a", "b", "c", "d"]);
+        assert_eq!(cell_lines("<b>x</b>"), vec!["<b>x</b>"]);
+        assert_eq!(cell_lines("- a\n  - b "), vec!["- a", "  - b"]);
+    }
+
+    #[test]
+    fn test_pad_cell_alignment() {
+        assert_eq!(pad_cell("ab", 5, Alignment::None), "ab   ");
+        assert_eq!(pad_cell("ab", 5, Alignment::Right), "   ab");
+        assert_eq!(pad_cell("ab", 5, Alignment::Center), " ab  ");
+        assert_eq!(pad_cell("你好", 5, Alignment::Right), " 你好");
+    }
+
+    #[test]
+    fn test_to_markdown_pads_short_rows() {
+        let table = Table::new(strings(&["a", "b", "c"]), vec![strings(&["1"])]);
+        let output = table.to_markdown().expect("Should render");
+
+        for line in output.lines() {
+            assert_eq!(line.matches('|').count(), 4, "Line: {line}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Rendering of tables as Typst `#table(...)` calls.
+
+use crate::table::cell_lines;
+use crate::{Alignment, Table};
+
+/// Renders a table as a Typst `#table` with a `table.header` row.
+///
+/// Column alignment becomes the `align` argument, with unaligned columns set
+/// left. Markup characters in cell content are escaped and line breaks
+/// become Typst line breaks (`\`).
+///
+/// # Examples
+///
+/// ```
+/// use ftb::TableFormatter;
+///
+/// let table = TableFormatter::new()
+///     .parse_table("| Item | Cost |\n|-|-:|\n| tea | $5 |")
+///     .unwrap();
+/// assert_eq!(
+///     ftb::typst::write_table(&table),
+///     "#table(\n  columns: 2,\n  align: (left, right),\n  table.header([Item], [Cost]),\n  [tea], [\\$5],\n)\n"
+/// );
+/// ```
+#[must_use]
+pub fn write_table(table: &Table) -> String {
+    let num_columns = table.column_count();
+    let align: Vec<&str> = (0..num_columns)
+        .map(|col_i| match table.alignment(col_i) {
+            Alignment::None | Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+        })
+        .collect();
+
+    let mut output = format!("#table(\n  columns: {num_columns},\n");
+    output.push_str(&format!("  align: ({}),\n", align.join(", ")));
+    output.push_str(&format!(
+        "  table.header({}),\n",
+        content_blocks(&table.header, num_columns)
+    ));
+    for row in &table.rows {
+        output.push_str(&format!("  {},\n", content_blocks(row, num_columns)));
+    }
+    output.push_str(")\n");
+
+    output
+}
+
+/// Formats one row as comma-separated content blocks.
+fn content_blocks(row: &[String], num_columns: usize) -> String {
+    (0..num_columns)
+        .map(|col_i| {
+            format!(
+                "[{}]",
+                escape_cell(row.get(col_i).map_or("", String::as_str))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Escapes Typst markup characters and turns line breaks into `\`.
+///
+/// `/` is escaped so `//` does not start a comment, `=`, `-` or `+` at the
+/// start of a line so it does not become a heading or a list item, and the
+/// `.` or `)` after leading digits so `1. step` is not a numbered list item.
+fn escape_cell(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for (line_i, line) in cell_lines(cell).iter().enumerate() {
+        if line_i > 0 {
+            escaped.push_str(" \\ ");
+        }
+        let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        for (i, c) in line.chars().enumerate() {
+            let is_markup = matches!(
+                c,
+                '\\' | '*' | '_' | '`' | '$' | '#' | '[' | ']' | '<' | '>' | '@' | '~' | '/'
+            );
+            let starts_line = i == 0 && matches!(c, '=' | '-' | '+');
+            let numbers_line = digits > 0 && i == digits && matches!(c, '.' | ')');
+            if is_markup || starts_line || numbers_line {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes_markup_and_line_breaks() {
+        let mut table = Table::new(
+            vec!["a".to_string(), "b".to_string()],
+            vec![vec!["*bold* #x".to_string(), "one<br>[two]".to_string()]],
+        );
+        table.alignments = vec![Alignment::Center, Alignment::None];
+
+        assert_eq!(
+            write_table(&table),
+            "#table(\n  columns: 2,\n  align: (center, left),\n  table.header([a], [b]),\n  [\\*bold\\* \\#x], [one \\ \\[two\\]],\n)\n"
+        );
+    }
+
+    #[test]
+    fn test_escapes_comments_and_line_start_markup() {
+        assert_eq!(escape_cell("a // b"), "a \\/\\/ b");
+        assert_eq!(escape_cell("= x - y"), "\\= x - y");
+        assert_eq!(escape_cell("-1<br>+ 2"), "\\-1 \\ \\+ 2");
+    }
+
+    #[test]
+    fn test_escapes_numbered_list_markers() {
+        assert_eq!(escape_cell("1. step"), "1\\. step");
+        assert_eq!(escape_cell("a<br>12) x"), "a \\ 12\\) x");
+        assert_eq!(escape_cell("v1. 2.5"), "v1. 2.5");
+    }
+}
@@ -129,3 +129,196 @@ fn test_document_with_multiple_tables() {
 
     assert_eq!(output, expected);
 }
+
+#[test]
+fn test_check_document() {
+    let input = include_str!("fixtures/input/document_with_tables.txt");
+    let expected = include_str!("fixtures/expected/document_with_tables.txt");
+
+    let mut formatter = TableFormatter::new();
+    let changes = formatter.check_document_as(input, ftb::Dialect::Markdown);
+    assert_eq!(changes.len(), 2);
+    assert!(changes
+        .iter()
+        .all(|change| change.start_line < change.end_line));
+
+    let changes = formatter.check_document_as(expected, ftb::Dialect::Markdown);
+    assert!(changes.is_empty());
+}
+
+#[test]
+fn test_format_document_reports_skipped_tables() {
+    let input = "# Notes\n\n| a | b |\n| 1 | 2 |\n\n| ok |\n|-|\n";
+    let formatted =
+        TableFormatter::new().format_document_with_diagnostics_as(input, ftb::Dialect::Markdown);
+
+    assert_eq!(
+        formatted.text,
+        "# Notes\n\n| a | b |\n| 1 | 2 |\n\n| ok |\n|----|\n"
+    );
+    assert_eq!(formatted.diagnostics.len(), 1);
+    let diagnostic = &formatted.diagnostics[0];
+    assert_eq!((diagnostic.start_line, diagnostic.end_line), (2, 4));
+    assert!(matches!(
+        diagnostic.error,
+        ftb::TableError::InvalidStructure(_)
+    ));
+}
+
+#[test]
+fn test_table_edits_follow_cursor() {
+    let mut formatter = TableFormatter::new();
+    let mut document = "| name | qty |\n|-|-:|\n| tea | 2 |\n".to_string();
+    let mut cursor = document.find("tea").unwrap();
+
+    // Tab past the last cell, type into the new row, then move it above "tea"
+    for edit in [ftb::TableEdit::NextCell, ftb::TableEdit::NextCell] {
+        let edited = formatter.edit_table(&document, cursor, edit).unwrap();
+        (document, cursor) = (edited.text, edited.cursor);
+    }
+    document.insert_str(cursor, "milk");
+    let edited = formatter
+        .edit_table(&document, cursor, ftb::TableEdit::MoveRowUp)
+        .unwrap();
+
+    assert_eq!(
+        edited.text,
+        "| name | qty |\n|------|----:|\n| milk |     |\n| tea  | 2   |\n"
+    );
+    assert!(edited.text[edited.cursor..].starts_with("milk"));
+}
+
+#[test]
+fn test_csv_import() {
+    let input = include_str!("fixtures/input/spreadsheet.csv");
+    let expected = include_str!("fixtures/expected/spreadsheet_csv.txt");
+    let output = ftb::csv::to_markdown(input, &ftb::csv::CsvOptions::csv())
+        .expect("CSV conversion should succeed");
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_csv_import_measures_cjk_width() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ftb"))
+        .args(["--from", "csv", "--width", "cjk"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Should start ftb");
+    child
+        .stdin
+        .take()
+        .expect("Should open stdin")
+        .write_all("a,b\n①②,x\n".as_bytes())
+        .expect("Should write input");
+    let output = child.wait_with_output().expect("Should run ftb");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "| a    | b |\n|------|---|\n| ①② | x |\n"
+    );
+}
+
+#[test]
+fn test_document_table_export() {
+    let input = include_str!("fixtures/input/document_with_tables.txt");
+    let tables = TableFormatter::new().parse_document(input);
+    assert_eq!(tables.len(), 2);
+
+    let csv = ftb::csv::write_table(&tables[1], ',');
+    assert_eq!(
+        csv,
+        "Version,Date,Changes\n0.1.0,Oct 1,Initial release\n0.2.0,Oct 10,Document mode\n"
+    );
+}
+
+#[test]
+fn test_json_import() {
+    let input = include_str!("fixtures/input/pods.json");
+    let expected = include_str!("fixtures/expected/pods_json.txt");
+    let output = ftb::json::to_markdown(input, &ftb::json::JsonImportOptions::default())
+        .expect("JSON conversion should succeed");
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_html_import() {
+    let input = include_str!("fixtures/input/confluence.html");
+    let expected = include_str!("fixtures/expected/confluence_html.txt");
+    let output = ftb::html::to_markdown(input).expect("HTML conversion should succeed");
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_rst_document() {
+    let input = include_str!("fixtures/input/document.rst");
+    let expected = include_str!("fixtures/expected/document_rst.txt");
+
+    let mut formatter = TableFormatter::new();
+    let output = formatter.format_document_as(input, ftb::Dialect::Rst);
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_org_document() {
+    let input = include_str!("fixtures/input/notes.org");
+    let expected = include_str!("fixtures/expected/notes_org.txt");
+
+    let mut formatter = TableFormatter::new();
+    let output = formatter.format_document_as(input, ftb::Dialect::Org);
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_grid_import() {
+    let input = include_str!("fixtures/input/recipes.md");
+    let expected = include_str!("fixtures/expected/recipes_grid.txt");
+
+    let tables = ftb::grid::parse_tables(input);
+    assert_eq!(tables.len(), 1);
+    let output = tables[0].to_markdown().expect("Should render");
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_mediawiki_import() {
+    let input = include_str!("fixtures/input/release.wiki");
+    let expected = include_str!("fixtures/expected/release_wiki.txt");
+    let output = ftb::mediawiki::to_markdown(input).expect("MediaWiki conversion should succeed");
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_psql_import() {
+    let input = include_str!("fixtures/input/incident.psql");
+    let expected = include_str!("fixtures/expected/incident_psql.txt");
+    let output = ftb::sql::to_markdown(input).expect("psql conversion should succeed");
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_columns_import() {
+    let input = include_str!("fixtures/input/pods.txt");
+    let expected = include_str!("fixtures/expected/pods_columns.txt");
+    let output = ftb::columns::to_markdown(input, &ftb::columns::ColumnsOptions::default())
+        .expect("Column conversion should succeed");
+    assert_eq!(output, expected);
+}
+
+#[cfg(feature = "sheet")]
+#[test]
+fn test_sheet_to_markdown() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/input/roadmap.xlsx");
+    let expected = include_str!("fixtures/expected/roadmap_sheet.txt");
+    let output = ftb::sheet::to_markdown(&path, &ftb::sheet::SheetOptions::default())
+        .expect("Spreadsheet conversion should succeed");
+    assert_eq!(output, expected);
+}